@@ -1,4 +1,4 @@
-use rand::{Rng, RngCore};
+use rand::{Rng, RngCore, seq::index::sample};
 
 use crate::genotype::Genotype;
 
@@ -31,6 +31,265 @@ impl CrossoverMethod for UniformCrossover {
     }
 }
 
+/// Child made of alternating segments of both parents, cut at `points`
+/// distinct random positions
+#[derive(Debug, Clone)]
+pub struct KPointCrossover {
+    pub points: usize,
+}
+
+impl CrossoverMethod for KPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        // cutting before the first gene would not change anything
+        let cuts = self.points.min(parent_a.len().saturating_sub(1));
+        let mut points = sample(rng, parent_a.len().saturating_sub(1), cuts).into_vec();
+        points.sort_unstable();
+
+        let mut points = points.into_iter().map(|point| point + 1).peekable();
+        let mut from_a = true;
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .enumerate()
+            .map(|(i, (&a, &b))| {
+                if points.next_if_eq(&i).is_some() {
+                    from_a = !from_a;
+                }
+                if from_a { a } else { b }
+            })
+            .collect()
+    }
+}
+
+/// [`KPointCrossover`] with a single cut
+#[derive(Debug, Clone)]
+pub struct SinglePointCrossover;
+
+impl CrossoverMethod for SinglePointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        KPointCrossover { points: 1 }.crossover(rng, parent_a, parent_b)
+    }
+}
+
+/// [`KPointCrossover`] with two cuts
+#[derive(Debug, Clone)]
+pub struct TwoPointCrossover;
+
+impl CrossoverMethod for TwoPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        KPointCrossover { points: 2 }.crossover(rng, parent_a, parent_b)
+    }
+}
+
+/// Every gene `alpha * a + (1 - alpha) * b`, with a fixed `alpha`
+#[derive(Debug, Clone)]
+pub struct WholeArithmeticCrossover {
+    pub alpha: f32,
+}
+
+impl CrossoverMethod for WholeArithmeticCrossover {
+    fn crossover(
+        &self,
+        _rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| self.alpha * a + (1.0 - self.alpha) * b)
+            .collect()
+    }
+}
+
+/// [`WholeArithmeticCrossover`] with a new random `alpha` between 0 and 1
+/// for every child
+#[derive(Debug, Clone)]
+pub struct ArithmeticCrossover;
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        WholeArithmeticCrossover {
+            alpha: rng.random(),
+        }
+        .crossover(rng, parent_a, parent_b)
+    }
+}
+
+/// BLX-α: every gene drawn uniformly between both parents' genes, widened
+/// on each side by `alpha` times their distance
+#[derive(Debug, Clone)]
+pub struct BlendCrossover {
+    pub alpha: f32,
+}
+
+impl CrossoverMethod for BlendCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        assert_eq!(parent_a.len(), parent_b.len());
+        assert!(self.alpha >= 0.0);
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let margin = self.alpha * (a - b).abs();
+                let (min, max) = (a.min(b) - margin, a.max(b) + margin);
+                if min < max {
+                    rng.random_range(min..=max)
+                } else {
+                    a
+                }
+            })
+            .collect()
+    }
+}
+
+/// SBX: children spread around their parents like single-point crossover
+/// does for binary genes; the larger `eta`, the closer to the parents
+#[derive(Debug, Clone)]
+pub struct SimulatedBinaryCrossover {
+    pub eta: f32,
+}
+
+impl CrossoverMethod for SimulatedBinaryCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        assert_eq!(parent_a.len(), parent_b.len());
+        assert!(self.eta >= 0.0);
+
+        let exponent = 1.0 / (self.eta + 1.0);
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let u: f32 = rng.random();
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(exponent)
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(exponent)
+                };
+                // either of the two symmetric children SBX defines
+                let (a, b) = if rng.random_bool(0.5) { (a, b) } else { (b, a) };
+                0.5 * ((1.0 + beta) * a + (1.0 - beta) * b)
+            })
+            .collect()
+    }
+}
+
+/// Applies `crossover` with the given `probability`, otherwise copies one
+/// of the parents, picked at random
+#[derive(Debug, Clone)]
+pub struct ProbabilisticCrossover<C> {
+    probability: f32,
+    crossover: C,
+}
+
+impl<C> ProbabilisticCrossover<C> {
+    pub fn new(probability: f32, crossover: C) -> Self {
+        assert!((0.0..=1.0).contains(&probability));
+
+        Self {
+            probability,
+            crossover,
+        }
+    }
+}
+
+impl<C> CrossoverMethod for ProbabilisticCrossover<C>
+where
+    C: CrossoverMethod,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        if rng.random_bool(self.probability as f64) {
+            self.crossover.crossover(rng, parent_a, parent_b)
+        } else if rng.random_bool(0.5) {
+            parent_a.clone()
+        } else {
+            parent_b.clone()
+        }
+    }
+}
+
+/// Uniform crossover over groups of consecutive genes instead of single
+/// genes, e.g. swapping whole neurons of a network so that genes tuned to
+/// work together are inherited together.
+///
+/// `groups` are the sizes of the groups, in order, adding up to the length
+/// of the genotypes.
+#[derive(Debug, Clone)]
+pub struct NeuronCrossover {
+    groups: Vec<usize>,
+}
+
+impl NeuronCrossover {
+    pub fn new(groups: Vec<usize>) -> Self {
+        Self { groups }
+    }
+}
+
+impl CrossoverMethod for NeuronCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Genotype,
+        parent_b: &Genotype,
+    ) -> Genotype {
+        assert_eq!(parent_a.len(), parent_b.len());
+        assert_eq!(self.groups.iter().sum::<usize>(), parent_a.len());
+
+        let mut child = Vec::with_capacity(parent_a.len());
+        let mut start = 0;
+        for &size in &self.groups {
+            let parent = if rng.random_bool(0.5) {
+                parent_a
+            } else {
+                parent_b
+            };
+            child.extend_from_slice(&parent.genes[start..start + size]);
+            start += size;
+        }
+        Genotype { genes: child }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -62,4 +321,113 @@ mod tests {
         assert_eq!(diff_a, 49);
         assert_eq!(diff_b, 51);
     }
+
+    fn parents() -> (Genotype, Genotype) {
+        (
+            (1..=10).map(|i| i as f32).collect(),
+            (1..=10).map(|i| -i as f32).collect(),
+        )
+    }
+
+    /// Number of switches between genes of `parent_a` and `parent_b`
+    fn segments(child: &Genotype) -> usize {
+        child
+            .iter()
+            .zip(child.iter().skip(1))
+            .filter(|(a, b)| a.signum() != b.signum())
+            .count()
+    }
+
+    #[test]
+    fn k_point_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        for points in 1..=4 {
+            let child = KPointCrossover { points }.crossover(&mut rng, &parent_a, &parent_b);
+
+            assert_eq!(segments(&child), points);
+            assert_eq!(child[0], 1.0);
+            assert!(
+                child
+                    .iter()
+                    .enumerate()
+                    .all(|(i, gene)| gene.abs() == (i + 1) as f32)
+            );
+        }
+
+        let child = SinglePointCrossover.crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(segments(&child), 1);
+        let child = TwoPointCrossover.crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(segments(&child), 2);
+    }
+
+    #[test]
+    fn whole_arithmetic_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = [1.0, 2.0].into_iter().collect();
+        let parent_b = [3.0, 0.0].into_iter().collect();
+
+        let child =
+            WholeArithmeticCrossover { alpha: 0.25 }.crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(child, [2.5, 0.5].into_iter().collect());
+    }
+
+    #[test]
+    fn blend_crossover_stays_within_the_widened_range() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = BlendCrossover { alpha: 0.5 }.crossover(&mut rng, &parent_a, &parent_b);
+
+        assert!(child.iter().enumerate().all(|(i, gene)| {
+            let bound = 2.0 * (i + 1) as f32;
+            (-bound..=bound).contains(gene)
+        }));
+    }
+
+    #[test]
+    fn simulated_binary_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = (0..1000).map(|_| 1.0).collect();
+        let parent_b = (0..1000).map(|_| -1.0).collect();
+
+        let child = SimulatedBinaryCrossover { eta: 2.0 }.crossover(&mut rng, &parent_a, &parent_b);
+
+        // spread symmetrically around the parents' mean, half of them
+        // between the parents and the other half beyond them
+        let mean = child.iter().sum::<f32>() / child.len() as f32;
+        assert!(mean.abs() < 0.1);
+        let between = child.iter().filter(|gene| gene.abs() <= 1.0).count();
+        assert!((450..550).contains(&between));
+    }
+
+    #[test]
+    fn probabilistic_crossover_copies_a_parent() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = ProbabilisticCrossover::new(0.0, UniformCrossover)
+            .crossover(&mut rng, &parent_a, &parent_b);
+
+        assert!(child == parent_a || child == parent_b);
+    }
+
+    #[test]
+    fn neuron_crossover_keeps_groups_together() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        let groups = vec![3, 3, 4];
+
+        for _ in 0..10 {
+            let child =
+                NeuronCrossover::new(groups.clone()).crossover(&mut rng, &parent_a, &parent_b);
+
+            assert!(segments(&child) <= 2);
+            for boundary in [1, 2, 4, 5, 7, 8, 9] {
+                assert_eq!(child[boundary].signum(), child[boundary - 1].signum());
+            }
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use crate::{Genotype, Individual};
+
+/// How spread out the genotypes of a population are; values close to zero
+/// mean the population has converged
+#[derive(Debug, Clone)]
+pub struct Diversity {
+    /// Average Euclidean distance over all pairs of genotypes
+    pub mean_distance: f32,
+    /// Variance of every gene across the population
+    pub gene_variance: Vec<f32>,
+    /// Average genotype
+    pub centroid: Genotype,
+}
+
+impl Diversity {
+    /// `None` when the genotypes are not all of the same length, e.g. for
+    /// NEAT genomes of different shapes
+    pub fn new<I>(population: &[I]) -> Option<Self>
+    where
+        I: Individual,
+    {
+        assert!(!population.is_empty());
+
+        let genotypes: Vec<&Genotype> = population.iter().map(Individual::genotype).collect();
+        let len = genotypes[0].len();
+        if genotypes.iter().any(|genotype| genotype.len() != len) {
+            return None;
+        }
+
+        let centroid = centroid(&genotypes);
+        let gene_variance = (0..len)
+            .map(|gene| {
+                genotypes
+                    .iter()
+                    .map(|genotype| (genotype[gene] - centroid[gene]).powi(2))
+                    .sum::<f32>()
+                    / genotypes.len() as f32
+            })
+            .collect();
+
+        Some(Self {
+            mean_distance: mean_pairwise_distance(&genotypes),
+            gene_variance,
+            centroid,
+        })
+    }
+
+    /// Variance averaged over all genes
+    pub fn mean_gene_variance(&self) -> f32 {
+        if self.gene_variance.is_empty() {
+            return 0.0;
+        }
+        self.gene_variance.iter().sum::<f32>() / self.gene_variance.len() as f32
+    }
+}
+
+/// Average of `genotypes`, which must all be of the same length
+pub fn centroid(genotypes: &[&Genotype]) -> Genotype {
+    assert!(!genotypes.is_empty());
+
+    let mut sum = vec![0.0; genotypes[0].len()];
+    for genotype in genotypes {
+        assert_eq!(genotype.len(), sum.len());
+        for (sum, gene) in sum.iter_mut().zip(genotype.iter()) {
+            *sum += gene;
+        }
+    }
+
+    sum.into_iter()
+        .map(|sum| sum / genotypes.len() as f32)
+        .collect()
+}
+
+/// Average Euclidean distance over all pairs of `genotypes`, 0 for fewer
+/// than two
+pub fn mean_pairwise_distance(genotypes: &[&Genotype]) -> f32 {
+    let mut sum = 0.0;
+    let mut pairs = 0;
+    for (i, a) in genotypes.iter().enumerate() {
+        for b in &genotypes[i + 1..] {
+            sum += a.euclidean_distance(b);
+            pairs += 1;
+        }
+    }
+
+    if pairs > 0 { sum / pairs as f32 } else { 0.0 }
+}
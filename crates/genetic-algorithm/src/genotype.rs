@@ -21,6 +21,39 @@ impl Genotype {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f32> {
         self.genes.iter_mut()
     }
+
+    pub fn euclidean_distance(&self, other: &Self) -> f32 {
+        assert_eq!(self.len(), other.len());
+
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> f32 {
+        assert_eq!(self.len(), other.len());
+
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum()
+    }
+
+    /// Cosine of the angle between both genotypes, from -1 for opposite
+    /// genes to 1 for proportional ones; 0 when either is all zeros
+    pub fn cosine_similarity(&self, other: &Self) -> f32 {
+        assert_eq!(self.len(), other.len());
+
+        let dot: f32 = self.iter().zip(other.iter()).map(|(a, b)| a * b).sum();
+        let norms = self.norm() * other.norm();
+        if norms > 0.0 { dot / norms } else { 0.0 }
+    }
+
+    fn norm(&self) -> f32 {
+        self.iter().map(|gene| gene * gene).sum::<f32>().sqrt()
+    }
 }
 
 impl PartialEq for Genotype {
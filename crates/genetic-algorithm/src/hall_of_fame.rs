@@ -0,0 +1,89 @@
+use crate::Individual;
+
+#[derive(Debug, Clone)]
+pub struct Entry<I> {
+    pub individual: I,
+    pub fitness: f32,
+    /// Generation the individual lived in
+    pub generation: usize,
+}
+
+/// Fittest individuals ever recorded, across generations
+#[derive(Debug, Clone)]
+pub struct HallOfFame<I> {
+    capacity: usize,
+    /// Fittest first, earlier generations first on ties
+    entries: Vec<Entry<I>>,
+}
+
+impl<I> HallOfFame<I> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+
+        Self {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Fittest first
+    pub fn entries(&self) -> &[Entry<I>] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&Entry<I>> {
+        self.entries.first()
+    }
+
+    /// Enters the individuals of `population` fitter than the current
+    /// entries, which should be recorded before it is evolved.
+    ///
+    /// Individuals already entered with the same genotype, e.g. elites
+    /// carried over, are only entered again when they got fitter, replacing
+    /// their previous entry.
+    pub fn record(&mut self, generation: usize, population: &[I])
+    where
+        I: Individual + Clone,
+    {
+        for individual in population {
+            let fitness = individual.fitness();
+            if let Some(index) = self
+                .entries
+                .iter()
+                .position(|entry| entry.individual.genotype() == individual.genotype())
+            {
+                if self.entries[index].fitness >= fitness {
+                    continue;
+                }
+                self.entries.remove(index);
+            }
+
+            let full = self.entries.len() == self.capacity;
+            if full
+                && self
+                    .entries
+                    .last()
+                    .is_some_and(|worst| worst.fitness >= fitness)
+            {
+                continue;
+            }
+
+            let position = self
+                .entries
+                .partition_point(|entry| entry.fitness >= fitness);
+            self.entries.insert(
+                position,
+                Entry {
+                    individual: individual.clone(),
+                    fitness,
+                    generation,
+                },
+            );
+            self.entries.truncate(self.capacity);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::stats::{Stats, StatsPopulation};
+
+const CSV_HEADER: &str = "generation,population,avg_fitness,min_fitness,max_fitness,median_fitness,std_fitness,lower_quartile,upper_quartile,best_index,mean_distance";
+
+/// [`Stats`] of every generation of a run, in order
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    generations: Vec<Stats>,
+}
+
+/// One line of [`History::write_jsonl`]
+#[derive(Serialize)]
+struct Record<'a> {
+    generation: usize,
+    population: &'static str,
+    avg_fitness: f32,
+    min_fitness: f32,
+    max_fitness: f32,
+    median_fitness: f32,
+    std_fitness: f32,
+    lower_quartile: f32,
+    upper_quartile: f32,
+    best_index: usize,
+    mean_distance: Option<f32>,
+    histogram: &'a [usize],
+}
+
+impl<'a> From<&'a Stats> for Record<'a> {
+    fn from(stats: &'a Stats) -> Self {
+        Self {
+            generation: stats.generation,
+            population: match stats.population {
+                StatsPopulation::Parents => "parents",
+                StatsPopulation::Offspring => "offspring",
+            },
+            avg_fitness: stats.avg_fitness,
+            min_fitness: stats.min_fitness,
+            max_fitness: stats.max_fitness,
+            median_fitness: stats.median_fitness,
+            std_fitness: stats.std_fitness,
+            lower_quartile: stats.quartiles.0,
+            upper_quartile: stats.quartiles.1,
+            best_index: stats.best_index,
+            mean_distance: stats
+                .diversity
+                .as_ref()
+                .map(|diversity| diversity.mean_distance),
+            histogram: &stats.histogram,
+        }
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, stats: Stats) {
+        self.generations.push(stats);
+    }
+
+    pub fn len(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generations.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Stats> {
+        self.generations.iter()
+    }
+
+    pub fn last(&self) -> Option<&Stats> {
+        self.generations.last()
+    }
+
+    /// Generation with the highest maximum fitness, the earliest on ties
+    pub fn best_generation(&self) -> Option<&Stats> {
+        self.generations.iter().reduce(|best, stats| {
+            if stats.max_fitness > best.max_fitness {
+                stats
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Average fitness of every generation averaged with up to `window - 1`
+    /// generations before it
+    pub fn moving_average(&self, window: usize) -> Vec<f32> {
+        assert!(window > 0);
+
+        (0..self.generations.len())
+            .map(|end| {
+                let start = (end + 1).saturating_sub(window);
+                let stats = &self.generations[start..=end];
+                stats.iter().map(|stats| stats.avg_fitness).sum::<f32>() / stats.len() as f32
+            })
+            .collect()
+    }
+
+    /// Number of generations since the maximum fitness last beat the best
+    /// one before it by more than `tolerance`
+    pub fn generations_without_improvement(&self, tolerance: f32) -> usize {
+        let mut best = f32::NEG_INFINITY;
+        let mut since = 0;
+        for stats in &self.generations {
+            if stats.max_fitness > best + tolerance {
+                best = stats.max_fitness;
+                since = 0;
+            } else {
+                since += 1;
+            }
+        }
+        since
+    }
+
+    /// Whether the maximum fitness has not improved by more than
+    /// `tolerance` over the last `generations`
+    pub fn is_plateau(&self, generations: usize, tolerance: f32) -> bool {
+        self.generations_without_improvement(tolerance) >= generations
+    }
+
+    /// One line per generation, the histogram left out
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{CSV_HEADER}")?;
+        for stats in &self.generations {
+            let record = Record::from(stats);
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                record.generation,
+                record.population,
+                record.avg_fitness,
+                record.min_fitness,
+                record.max_fitness,
+                record.median_fitness,
+                record.std_fitness,
+                record.lower_quartile,
+                record.upper_quartile,
+                record.best_index,
+                record
+                    .mean_distance
+                    .map_or(String::new(), |distance| distance.to_string()),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// One JSON object per line and generation
+    pub fn write_jsonl(&self, mut writer: impl Write) -> io::Result<()> {
+        for stats in &self.generations {
+            serde_json::to_writer(&mut writer, &Record::from(stats))?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Extend<Stats> for History {
+    fn extend<T: IntoIterator<Item = Stats>>(&mut self, iter: T) {
+        self.generations.extend(iter);
+    }
+}
+
+impl FromIterator<Stats> for History {
+    fn from_iter<T: IntoIterator<Item = Stats>>(iter: T) -> Self {
+        Self {
+            generations: iter.into_iter().collect(),
+        }
+    }
+}
@@ -2,23 +2,50 @@ use crossover_method::CrossoverMethod;
 pub use genotype::*;
 pub use individual::Individual;
 use mutation_method::MutationMethod;
-use rand::RngCore;
+use rand::{RngCore, seq::SliceRandom};
 use selection_method::SelectionMethod;
 
-use crate::stats::Stats;
+use std::mem;
+
+use crate::{
+    observer::{NoObserver, Observer},
+    stats::{Stats, StatsPopulation},
+    termination::{Criterion, Run},
+};
 
 pub mod crossover_method;
+pub mod diversity;
 mod genotype;
+pub mod hall_of_fame;
+pub mod history;
 mod individual;
 pub mod mutation_method;
+pub mod observer;
 pub mod selection_method;
 pub mod stats;
+pub mod termination;
 
 #[derive(Debug, Clone)]
-pub struct GeneticAlgorithm<S, C, M> {
+pub struct GeneticAlgorithm<S, C, M, O = NoObserver> {
     pub selection_method: S,
     pub crossover_method: C,
     pub mutation_method: M,
+    /// Number of fittest individuals carried over unchanged to the next
+    /// generation
+    pub elitism: usize,
+    /// Population described by the stats returned by
+    /// [`GeneticAlgorithm::evolve`]
+    pub stats_population: StatsPopulation,
+    /// Whether the stats include the [`Diversity`](diversity::Diversity) of
+    /// the population, see [`Stats::with_diversity`]
+    pub diversity: bool,
+    pub observer: O,
+    generation: usize,
+    /// Fitness of the fittest parent of every child bred in the last
+    /// generation, for [`MutationMethod::adapt`]
+    parent_fitness: Vec<f32>,
+    /// Highest fitness seen so far, for [`Observer::on_new_best`]
+    best_fitness: f32,
 }
 
 impl<S, C, M> GeneticAlgorithm<S, C, M>
@@ -32,28 +59,248 @@ where
             selection_method,
             crossover_method,
             mutation_method,
+            elitism: 0,
+            stats_population: StatsPopulation::Parents,
+            diversity: false,
+            observer: NoObserver,
+            generation: 0,
+            parent_fitness: Vec::new(),
+            best_fitness: f32::NEG_INFINITY,
+        }
+    }
+}
+
+impl<S, C, M, O> GeneticAlgorithm<S, C, M, O>
+where
+    S: SelectionMethod,
+    C: CrossoverMethod,
+    M: MutationMethod,
+{
+    pub fn with_observer<P>(self, observer: P) -> GeneticAlgorithm<S, C, M, P> {
+        GeneticAlgorithm {
+            selection_method: self.selection_method,
+            crossover_method: self.crossover_method,
+            mutation_method: self.mutation_method,
+            elitism: self.elitism,
+            stats_population: self.stats_population,
+            diversity: self.diversity,
+            observer,
+            generation: self.generation,
+            parent_fitness: self.parent_fitness,
+            best_fitness: self.best_fitness,
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Stats)
+    pub fn with_elitism(mut self, elitism: usize) -> Self {
+        self.elitism = elitism;
+        self
+    }
+
+    pub fn with_stats_population(mut self, stats_population: StatsPopulation) -> Self {
+        self.stats_population = stats_population;
+        self
+    }
+
+    pub fn with_diversity(mut self, diversity: bool) -> Self {
+        self.diversity = diversity;
+        self
+    }
+
+    /// Number of generations evolved so far, i.e. the generation the next
+    /// call to [`GeneticAlgorithm::evolve`] evolves
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Stats)
+    where
+        I: Individual + Clone,
+        O: Observer<I>,
+    {
+        self.begin_generation(population);
+        let (next, parent_fitness) =
+            self.breed_generation(rng, population, |rng, parent_a, parent_b| {
+                let mut child =
+                    self.crossover_method
+                        .crossover(rng, parent_a.genotype(), parent_b.genotype());
+                self.observer.on_crossover(&child);
+                self.mutation_method.mutate(rng, &mut child);
+                self.observer.on_mutation(&child);
+                I::create(child)
+            });
+        self.parent_fitness = parent_fitness;
+        self.end_generation(population, next)
+    }
+
+    /// Like [`GeneticAlgorithm::evolve`], but children are bred from the
+    /// selected parents by `breed` instead of the crossover and mutation
+    /// methods, e.g. for individuals whose genes do not fit in a flat
+    /// [`Genotype`].
+    ///
+    /// Crossovers and mutations happen inside `breed`, so the observer is
+    /// not told of them.
+    pub fn evolve_with<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        breed: impl FnMut(&mut dyn RngCore, &I, &I) -> I,
+    ) -> (Vec<I>, Stats)
+    where
+        I: Individual + Clone,
+        O: Observer<I>,
+    {
+        self.begin_generation(population);
+        let (next, parent_fitness) = self.breed_generation(rng, population, breed);
+        self.parent_fitness = parent_fitness;
+        self.end_generation(population, next)
+    }
+
+    fn begin_generation<I>(&mut self, population: &[I])
     where
         I: Individual,
+        O: Observer<I>,
     {
         assert!(!population.is_empty());
 
-        (
-            (0..population.len())
-                .map(|_| {
-                    let parent_a = self.selection_method.select(rng, population).genotype();
-                    let parent_b = self.selection_method.select(rng, population).genotype();
-                    let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
-                    self.mutation_method.mutate(rng, &mut child);
-                    I::create(child)
-                })
-                .collect(),
-            Stats::new(population),
-        ) // let stats = Stats::new(new_population);
-        // (new_population, stats)
+        self.observer
+            .on_generation_start(self.generation, population);
+
+        let best = population
+            .iter()
+            .reduce(|best, individual| {
+                if individual.fitness() > best.fitness() {
+                    individual
+                } else {
+                    best
+                }
+            })
+            .unwrap();
+        if best.fitness() > self.best_fitness {
+            self.best_fitness = best.fitness();
+            self.observer.on_new_best(self.generation, best);
+        }
+
+        // the children of the last generation, bred after the elites, now
+        // know their fitness
+        let children = self.parent_fitness.len();
+        if children > 0 && children <= population.len() {
+            let successes = population[population.len() - children..]
+                .iter()
+                .zip(&self.parent_fitness)
+                .filter(|&(child, &parent)| child.fitness() > parent)
+                .count();
+            self.mutation_method
+                .adapt(successes as f32 / children as f32);
+        }
+    }
+
+    fn breed_generation<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        mut breed: impl FnMut(&mut dyn RngCore, &I, &I) -> I,
+    ) -> (Vec<I>, Vec<f32>)
+    where
+        I: Individual + Clone,
+        O: Observer<I>,
+    {
+        let elitism = self.elitism.min(population.len());
+        let mut ranked: Vec<&I> = population.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let mut next: Vec<I> = ranked[..elitism]
+            .iter()
+            .map(|&elite| elite.clone())
+            .collect();
+        // all at once, for selection methods spreading them out like
+        // stochastic universal sampling, then paired at random
+        let mut parents =
+            self.selection_method
+                .select_many(rng, population, 2 * (population.len() - elitism));
+        parents.shuffle(rng);
+
+        let mut parent_fitness = Vec::with_capacity(parents.len() / 2);
+        next.extend(parents.chunks_exact(2).map(|parents| {
+            self.observer.on_parents_selected(parents[0], parents[1]);
+            parent_fitness.push(parents[0].fitness().max(parents[1].fitness()));
+            breed(rng, parents[0], parents[1])
+        }));
+        (next, parent_fitness)
+    }
+
+    fn end_generation<I>(&mut self, population: &[I], next: Vec<I>) -> (Vec<I>, Stats)
+    where
+        I: Individual,
+        O: Observer<I>,
+    {
+        let mut stats = match self.stats_population {
+            StatsPopulation::Parents => self.stats(population, self.diversity),
+            StatsPopulation::Offspring => self.stats(&next, self.diversity),
+        };
+        stats.population = self.stats_population;
+
+        self.observer
+            .on_generation_end(self.generation, &next, &stats);
+        self.generation += 1;
+        (next, stats)
+    }
+
+    fn stats<I>(&self, population: &[I], diversity: bool) -> Stats
+    where
+        I: Individual,
+    {
+        let stats = Stats::new(population).with_generation(self.generation);
+        if diversity {
+            stats.with_diversity(population)
+        } else {
+            stats
+        }
+    }
+
+    /// Evolves `population` generation after generation until any of
+    /// `criteria` is met.
+    ///
+    /// Every generation is first passed to `evaluate`, which gives its
+    /// individuals their fitness, e.g. by letting them live for a while;
+    /// individuals knowing their fitness as soon as they are created can
+    /// be returned unchanged. Its stats are then recorded and the criteria
+    /// checked, and only if none is met is it evolved into the next one.
+    ///
+    /// The history describes the evaluated populations, whatever
+    /// [`GeneticAlgorithm::stats_population`] says, and includes their
+    /// diversity whenever [`Criterion::DiversityCollapse`] is among the
+    /// criteria, whatever [`GeneticAlgorithm::diversity`] says.
+    pub fn run_until<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        mut population: Vec<I>,
+        criteria: &[Criterion],
+        mut evaluate: impl FnMut(&mut dyn RngCore, Vec<I>) -> Vec<I>,
+    ) -> Run<I>
+    where
+        I: Individual + Clone,
+        O: Observer<I>,
+    {
+        let diversity = self.diversity
+            || criteria
+                .iter()
+                .any(|criterion| matches!(criterion, Criterion::DiversityCollapse(_)));
+
+        let mut evaluated = false;
+        let (reason, history) = termination::record_until(criteria, || {
+            if evaluated {
+                (population, _) = self.evolve(rng, &population);
+            }
+            evaluated = true;
+            population = evaluate(rng, mem::take(&mut population));
+            self.stats(&population, diversity)
+        });
+
+        Run {
+            population,
+            history,
+            reason,
+        }
     }
 }
 
@@ -114,7 +361,7 @@ mod tests {
         fn evolve() {
             let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-            let ga = GeneticAlgorithm::new(
+            let mut ga = GeneticAlgorithm::new(
                 RouletteWheelSelection,
                 UniformCrossover,
                 GaussianMutation::new(0.5, 0.5),
@@ -132,25 +379,97 @@ mod tests {
             }
 
             let expected_population = vec![
-                individual(&[2.440106, 3.443046, 1.4234216, 2.4990084, 1.2892904]),
-                individual(&[3.052356, 3.2864828, 2.2035873, 3.1747146, 3.6921844]),
-                individual(&[2.2522352, 3.1377535, 1.1986562, 2.4990084, 4.1539874]),
-                individual(&[2.3075106, 3.6501513, 1.9449277, 1.5516641, 2.8120282]),
+                individual(&[2.8686223, 2.1742518, 1.3947582, 3.890102, 2.435416]),
+                individual(&[2.8732371, 1.1099534, 3.479053, 4.653152, 1.6479475]),
+                individual(&[2.7370358, 1.8845661, 3.143267, 4.159903, 2.7378554]),
+                individual(&[2.8686223, 1.8845661, 3.143267, 4.1506944, 2.435416]),
             ];
 
             assert_eq!(expected_population, population);
         }
     }
 
+    mod elitism {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        use crate::{
+            GeneticAlgorithm, crossover_method::UniformCrossover, individual::Individual,
+            mutation_method::GaussianMutation, selection_method::RouletteWheelSelection,
+        };
+
+        use super::TestIndividual;
+
+        #[test]
+        fn keeps_the_fittest_unchanged() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(1.0, 1.0),
+            )
+            .with_elitism(2);
+
+            let population: Vec<TestIndividual> = [[1.0, 1.0], [4.0, 4.0], [0.0, 1.0], [3.0, 2.0]]
+                .into_iter()
+                .map(|genes| TestIndividual::create(genes.into_iter().collect()))
+                .collect();
+
+            let (evolved, _) = ga.evolve(&mut rng, &population);
+
+            assert_eq!(evolved.len(), population.len());
+            assert_eq!(evolved[0], population[1]);
+            assert_eq!(evolved[1], population[3]);
+            assert!(!evolved[2..].contains(&population[1]));
+        }
+    }
+
+    mod hall_of_fame {
+        use crate::{hall_of_fame::HallOfFame, individual::Individual, tests::TestIndividual};
+
+        fn individual(genes: &[f32]) -> TestIndividual {
+            TestIndividual::create(genes.iter().cloned().collect())
+        }
+
+        #[test]
+        fn keeps_the_fittest_ever_seen() {
+            let mut hall_of_fame = HallOfFame::new(3);
+
+            hall_of_fame.record(0, &[individual(&[2.0]), individual(&[5.0])]);
+            hall_of_fame.record(
+                1,
+                &[
+                    individual(&[1.0]),
+                    // carried over, not entered twice
+                    individual(&[5.0]),
+                    individual(&[3.0]),
+                    individual(&[2.0, 3.0]),
+                ],
+            );
+            hall_of_fame.record(2, &[individual(&[0.0])]);
+
+            let entries: Vec<(f32, usize)> = hall_of_fame
+                .entries()
+                .iter()
+                .map(|entry| (entry.fitness, entry.generation))
+                .collect();
+            assert_eq!(entries, vec![(5.0, 0), (5.0, 1), (3.0, 1)]);
+            assert_eq!(hall_of_fame.best().unwrap().individual, individual(&[5.0]));
+        }
+    }
+
     mod selection {
         use std::collections::BTreeMap;
 
-        use rand::SeedableRng;
+        use rand::{RngCore, SeedableRng};
         use rand_chacha::ChaCha8Rng;
 
         use crate::{
             individual::Individual,
-            selection_method::{RouletteWheelSelection, SelectionMethod},
+            selection_method::{
+                RankPressure, RankSelection, RouletteWheelSelection, SelectionMethod,
+                StochasticUniversalSampling, TournamentSelection, TruncationSelection,
+            },
             tests::TestIndividual,
         };
 
@@ -174,6 +493,183 @@ mod tests {
 
             assert_eq!(actual_histogram, exptected_histogram);
         }
+
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                TestIndividual::new(1.0),
+                TestIndividual::new(2.0),
+                TestIndividual::new(3.0),
+                TestIndividual::new(4.0),
+            ]
+        }
+
+        /// How many times each fitness was selected over 1000 selections
+        fn histogram(
+            method: &impl SelectionMethod,
+            population: &[TestIndividual],
+        ) -> BTreeMap<i32, i32> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut histogram = BTreeMap::new();
+            for _ in 0..1000 {
+                let fitness = method.select(&mut rng, population).fitness() as i32;
+                *histogram.entry(fitness).or_insert(0) += 1;
+            }
+            histogram
+        }
+
+        #[test]
+        fn roulette_wheel_selection_without_fitness() {
+            let population = vec![TestIndividual::new(0.0); 4];
+
+            let histogram = histogram(&RouletteWheelSelection, &population);
+
+            assert_eq!(histogram, BTreeMap::from_iter([(0, 1000)]));
+        }
+
+        #[test]
+        fn tournament_selection() {
+            let histogram = histogram(&TournamentSelection { size: 2 }, &population());
+
+            assert_eq!(
+                histogram,
+                BTreeMap::from_iter([(1, 57), (2, 198), (3, 336), (4, 409)])
+            );
+        }
+
+        #[test]
+        fn linear_rank_selection() {
+            let method = RankSelection {
+                pressure: RankPressure::Linear(2.0),
+            };
+
+            let histogram = histogram(&method, &population());
+
+            assert_eq!(
+                histogram,
+                BTreeMap::from_iter([(2, 164), (3, 337), (4, 499)])
+            );
+        }
+
+        #[test]
+        fn exponential_rank_selection() {
+            let method = RankSelection {
+                pressure: RankPressure::Exponential(0.5),
+            };
+            // the outlier is only as likely as the best of a regular population
+            let population = vec![
+                TestIndividual::new(1.0),
+                TestIndividual::new(2.0),
+                TestIndividual::new(3.0),
+                TestIndividual::new(400.0),
+            ];
+
+            let histogram = histogram(&method, &population);
+
+            assert_eq!(
+                histogram,
+                BTreeMap::from_iter([(1, 72), (2, 130), (3, 273), (400, 525)])
+            );
+        }
+
+        #[test]
+        fn stochastic_universal_sampling() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let selected: Vec<i32> = StochasticUniversalSampling
+                .select_many(&mut rng, &population(), 10)
+                .into_iter()
+                .map(|individual| individual.fitness() as i32)
+                .collect();
+
+            // exactly the expected number of times, as the fitnesses add up to 10
+            assert_eq!(selected, vec![1, 2, 2, 3, 3, 3, 4, 4, 4, 4]);
+            assert!(
+                StochasticUniversalSampling
+                    .select_many(&mut rng, &population(), 0)
+                    .is_empty()
+            );
+        }
+
+        /// Always yields zero bits, e.g. a wheel spun to exactly 0.0
+        struct ZeroRng;
+
+        impl RngCore for ZeroRng {
+            fn next_u32(&mut self) -> u32 {
+                0
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                0
+            }
+
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                dst.fill(0);
+            }
+        }
+
+        #[test]
+        fn stochastic_universal_sampling_skips_zero_fitness() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![
+                TestIndividual::new(0.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(0.0),
+                TestIndividual::new(3.0),
+            ];
+
+            for rng in [&mut rng as &mut dyn RngCore, &mut ZeroRng] {
+                let selected: Vec<i32> = StochasticUniversalSampling
+                    .select_many(rng, &population, 4)
+                    .into_iter()
+                    .map(|individual| individual.fitness() as i32)
+                    .collect();
+
+                assert_eq!(selected, vec![1, 3, 3, 3]);
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "fitness must not be negative")]
+        fn roulette_wheel_selection_with_negative_fitness() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![TestIndividual::new(-1.0), TestIndividual::new(2.0)];
+
+            RouletteWheelSelection.select(&mut rng, &population);
+        }
+
+        #[test]
+        #[should_panic(expected = "fitness must not be negative")]
+        fn stochastic_universal_sampling_with_negative_fitness() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![TestIndividual::new(-1.0), TestIndividual::new(2.0)];
+
+            StochasticUniversalSampling.select_many(&mut rng, &population, 2);
+        }
+
+        #[test]
+        fn rank_and_truncation_select_many() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = population();
+            let rank = RankSelection {
+                pressure: RankPressure::Linear(2.0),
+            };
+
+            let selected = rank.select_many(&mut rng, &population, 100);
+            assert_eq!(selected.len(), 100);
+            assert!(selected.iter().all(|individual| individual.fitness() > 1.0));
+
+            let selected =
+                TruncationSelection { fraction: 0.5 }.select_many(&mut rng, &population, 100);
+            assert_eq!(selected.len(), 100);
+            assert!(selected.iter().all(|individual| individual.fitness() > 2.0));
+        }
+
+        #[test]
+        fn truncation_selection() {
+            let histogram = histogram(&TruncationSelection { fraction: 0.5 }, &population());
+
+            assert_eq!(histogram, BTreeMap::from_iter([(3, 501), (4, 499)]));
+        }
     }
 
     mod crossover {
@@ -210,6 +706,502 @@ mod tests {
         }
     }
 
+    mod stats {
+        use approx::assert_relative_eq;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        use crate::{
+            GeneticAlgorithm,
+            crossover_method::UniformCrossover,
+            individual::Individual,
+            mutation_method::GaussianMutation,
+            selection_method::RouletteWheelSelection,
+            stats::{Stats, StatsPopulation},
+            tests::TestIndividual,
+        };
+
+        #[test]
+        fn describes_the_fitness_distribution() {
+            let population: Vec<TestIndividual> = [4.0, 1.0, 8.0, 2.0, 5.0, 10.0, 3.0, 7.0]
+                .map(|fitness| TestIndividual::create([fitness].into_iter().collect()))
+                .into();
+
+            let stats = Stats::new(&population).with_generation(3);
+
+            assert_relative_eq!(stats.avg_fitness, 5.0);
+            assert_relative_eq!(stats.median_fitness, 4.5);
+            assert_relative_eq!(stats.std_fitness, 2.9154759);
+            assert_relative_eq!(stats.quartiles.0, 2.75);
+            assert_relative_eq!(stats.quartiles.1, 7.25);
+            // bins 0.9 wide from 1.0
+            assert_eq!(stats.histogram, vec![1, 1, 1, 1, 1, 0, 1, 1, 0, 1]);
+            assert_eq!(stats.best_index, 5);
+            assert_eq!(stats.generation, 3);
+        }
+
+        #[test]
+        fn same_fitness_everywhere() {
+            let population = vec![TestIndividual::create([2.0].into_iter().collect()); 3];
+
+            let stats = Stats::new(&population);
+
+            assert_relative_eq!(stats.std_fitness, 0.0);
+            assert_eq!(stats.histogram[0], 3);
+            assert_eq!(stats.best_index, 0);
+        }
+
+        #[test]
+        fn describes_the_chosen_population() {
+            let population: Vec<TestIndividual> = [[1.0, 1.0], [4.0, 4.0], [0.0, 1.0]]
+                .map(|genes| TestIndividual::create(genes.into_iter().collect()))
+                .into();
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(1.0, 1.0),
+            );
+
+            let (_, parents) =
+                ga.evolve(&mut ChaCha8Rng::from_seed(Default::default()), &population);
+            let (offspring, stats) = ga
+                .with_stats_population(StatsPopulation::Offspring)
+                .evolve(&mut ChaCha8Rng::from_seed(Default::default()), &population);
+
+            assert_eq!(parents.population, StatsPopulation::Parents);
+            assert_relative_eq!(parents.max_fitness, 8.0);
+            assert_eq!(stats.population, StatsPopulation::Offspring);
+            assert_relative_eq!(stats.avg_fitness, Stats::new(&offspring).avg_fitness);
+        }
+    }
+
+    mod observer {
+        use std::cell::{Cell, RefCell};
+
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        use crate::{
+            GeneticAlgorithm, Genotype, crossover_method::UniformCrossover, individual::Individual,
+            mutation_method::GaussianMutation, observer::Observer,
+            selection_method::RouletteWheelSelection, stats::Stats, tests::TestIndividual,
+        };
+
+        #[derive(Default)]
+        struct Recorder {
+            events: RefCell<Vec<String>>,
+            children: Cell<usize>,
+        }
+
+        impl Observer<TestIndividual> for Recorder {
+            fn on_generation_start(&self, generation: usize, _: &[TestIndividual]) {
+                self.events.borrow_mut().push(format!("start {generation}"));
+            }
+
+            fn on_new_best(&self, generation: usize, individual: &TestIndividual) {
+                self.events
+                    .borrow_mut()
+                    .push(format!("best {generation} {}", individual.fitness()));
+            }
+
+            fn on_parents_selected(&self, _: &TestIndividual, _: &TestIndividual) {
+                self.children.set(self.children.get() + 1);
+            }
+
+            fn on_crossover(&self, _: &Genotype) {
+                self.events.borrow_mut().push("crossover".into());
+            }
+
+            fn on_mutation(&self, _: &Genotype) {
+                self.events.borrow_mut().push("mutation".into());
+            }
+
+            fn on_generation_end(
+                &self,
+                generation: usize,
+                offspring: &[TestIndividual],
+                _: &Stats,
+            ) {
+                self.events
+                    .borrow_mut()
+                    .push(format!("end {generation} {}", offspring.len()));
+            }
+        }
+
+        #[test]
+        fn is_notified_of_every_stage() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(0.0, 0.0),
+            )
+            .with_observer(Recorder::default());
+            let population = vec![
+                TestIndividual::create([1.0].into_iter().collect()),
+                TestIndividual::create([3.0].into_iter().collect()),
+            ];
+
+            let (population, _) = ga.evolve(&mut rng, &population);
+            let (_, stats) = ga.evolve(&mut rng, &population);
+
+            let events = ga.observer.events.borrow();
+            assert_eq!(
+                &events[..7],
+                [
+                    "start 0",
+                    "best 0 3",
+                    "crossover",
+                    "mutation",
+                    "crossover",
+                    "mutation",
+                    "end 0 2"
+                ]
+            );
+            assert_eq!(events[7], "start 1");
+            // without mutation, the children cannot beat the best parent
+            assert!(!events.iter().any(|event| event.starts_with("best 1")));
+            assert_eq!(ga.observer.children.get(), 4);
+            assert_eq!(ga.generation(), 2);
+            assert_eq!(stats.generation, 1);
+        }
+    }
+
+    mod termination {
+        use std::time::Duration;
+
+        use rand::{RngCore, SeedableRng};
+        use rand_chacha::ChaCha8Rng;
+
+        use crate::{
+            GeneticAlgorithm,
+            crossover_method::UniformCrossover,
+            diversity::Diversity,
+            history::History,
+            individual::Individual,
+            mutation_method::GaussianMutation,
+            selection_method::TournamentSelection,
+            stats::Stats,
+            termination::{Criterion, first_met},
+            tests::TestIndividual,
+        };
+
+        fn ga() -> GeneticAlgorithm<TournamentSelection, UniformCrossover, GaussianMutation> {
+            GeneticAlgorithm::new(
+                TournamentSelection { size: 2 },
+                UniformCrossover,
+                GaussianMutation::new(0.5, 0.5),
+            )
+        }
+
+        fn population() -> Vec<TestIndividual> {
+            (0..8)
+                .map(|i| TestIndividual::create([i as f32, 0.0, 1.0].into_iter().collect()))
+                .collect()
+        }
+
+        fn unchanged(_: &mut dyn RngCore, population: Vec<TestIndividual>) -> Vec<TestIndividual> {
+            population
+        }
+
+        #[test]
+        fn stops_after_max_generations() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let run = ga().run_until(
+                &mut rng,
+                population(),
+                &[
+                    Criterion::TargetFitness(f32::INFINITY),
+                    Criterion::MaxGenerations(5),
+                ],
+                unchanged,
+            );
+
+            assert_eq!(run.reason, Criterion::MaxGenerations(5));
+            assert_eq!(run.history.len(), 5);
+            assert_eq!(run.history.last().unwrap().generation, 4);
+            assert_eq!(run.population.len(), 8);
+        }
+
+        #[test]
+        fn does_not_evolve_the_last_generation() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut ga = ga();
+
+            for generations in [0, 3] {
+                let mut evaluations = 0;
+                let run = ga.run_until(
+                    &mut rng,
+                    population(),
+                    &[Criterion::MaxGenerations(generations)],
+                    |_, population| {
+                        evaluations += 1;
+                        population
+                    },
+                );
+
+                assert_eq!(evaluations, generations);
+                assert_eq!(run.history.len(), generations);
+            }
+            // none for the first run, two for the second one
+            assert_eq!(ga.generation(), 2);
+        }
+
+        #[test]
+        fn stops_once_the_target_is_reached() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let criteria = [
+                Criterion::TargetFitness(12.0),
+                Criterion::MaxGenerations(1000),
+            ];
+
+            let run = ga().run_until(&mut rng, population(), &criteria, unchanged);
+
+            assert_eq!(run.reason, Criterion::TargetFitness(12.0));
+            assert!(run.history.len() < 1000);
+            assert!(
+                run.population
+                    .iter()
+                    .any(|individual| individual.fitness() >= 12.0)
+            );
+        }
+
+        #[test]
+        fn evaluates_every_generation() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut evaluations = 0;
+
+            let run = ga().run_until(
+                &mut rng,
+                population(),
+                &[Criterion::TimeLimit(Duration::ZERO)],
+                |_, population| {
+                    evaluations += 1;
+                    population
+                },
+            );
+
+            assert_eq!(run.reason, Criterion::TimeLimit(Duration::ZERO));
+            assert_eq!(evaluations, 1);
+        }
+
+        #[test]
+        fn diversity_collapses_without_being_enabled() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            // without mutations, drift leaves copies of a single genotype
+            let mut ga = GeneticAlgorithm::new(
+                TournamentSelection { size: 2 },
+                UniformCrossover,
+                GaussianMutation::new(0.0, 0.0),
+            );
+            let criteria = [
+                Criterion::DiversityCollapse(0.01),
+                Criterion::MaxGenerations(1000),
+            ];
+
+            let run = ga.run_until(&mut rng, population(), &criteria, unchanged);
+
+            assert!(!ga.diversity);
+            assert_eq!(run.reason, Criterion::DiversityCollapse(0.01));
+            assert!(run.history.iter().all(|stats| stats.diversity.is_some()));
+        }
+
+        #[test]
+        fn stagnation_and_diversity_collapse() {
+            let converged = vec![TestIndividual::create([1.0, 1.0].into_iter().collect()); 3];
+            let history: History = (0..4)
+                .map(|generation| {
+                    Stats::new(&converged)
+                        .with_generation(generation)
+                        .with_diversity(&converged)
+                })
+                .collect();
+            let stagnation = Criterion::Stagnation {
+                generations: 3,
+                tolerance: 0.0,
+            };
+
+            assert!(Diversity::new(&converged).is_some());
+            assert_eq!(
+                first_met(
+                    &[Criterion::DiversityCollapse(0.01), stagnation],
+                    &history,
+                    Duration::ZERO
+                ),
+                Some(Criterion::DiversityCollapse(0.01))
+            );
+            assert!(stagnation.is_met(&history, Duration::ZERO));
+            assert!(!stagnation.is_met(&history.iter().take(3).cloned().collect(), Duration::ZERO));
+            assert_eq!(
+                first_met(&[stagnation], &History::new(), Duration::MAX),
+                None
+            );
+        }
+    }
+
+    mod history {
+        use approx::assert_relative_eq;
+
+        use crate::{history::History, stats::Stats};
+
+        fn stats(generation: usize, avg_fitness: f32, max_fitness: f32) -> Stats {
+            Stats {
+                avg_fitness,
+                max_fitness,
+                generation,
+                ..Stats::default()
+            }
+        }
+
+        fn history() -> History {
+            [(1.0, 2.0), (2.0, 5.0), (3.0, 5.05), (4.0, 4.0), (6.0, 5.0)]
+                .into_iter()
+                .enumerate()
+                .map(|(generation, (avg, max))| stats(generation, avg, max))
+                .collect()
+        }
+
+        #[test]
+        fn queries() {
+            let history = history();
+
+            assert_eq!(history.best_generation().unwrap().generation, 2);
+            assert_relative_eq!(
+                history.moving_average(2).as_slice(),
+                [1.0, 1.5, 2.5, 3.5, 5.0].as_ref()
+            );
+            assert_eq!(history.generations_without_improvement(0.0), 2);
+            assert_eq!(history.generations_without_improvement(0.1), 3);
+            assert!(history.is_plateau(3, 0.1));
+            assert!(!history.is_plateau(3, 0.0));
+        }
+
+        #[test]
+        fn csv() {
+            let history: History = [stats(0, 1.5, 2.0)].into_iter().collect();
+            let mut csv = Vec::new();
+
+            history.write_csv(&mut csv).unwrap();
+
+            let csv = String::from_utf8(csv).unwrap();
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines.len(), 2);
+            assert!(lines[0].starts_with("generation,population,avg_fitness,"));
+            assert_eq!(lines[1], "0,parents,1.5,0,2,0,0,0,0,0,");
+        }
+
+        #[test]
+        fn jsonl() {
+            let mut jsonl = Vec::new();
+
+            history().write_jsonl(&mut jsonl).unwrap();
+
+            let jsonl = String::from_utf8(jsonl).unwrap();
+            let lines: Vec<serde_json::Value> = jsonl
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            assert_eq!(lines.len(), 5);
+            assert_eq!(lines[4]["generation"], 4);
+            assert_eq!(lines[4]["avg_fitness"], 6.0);
+            assert_eq!(lines[4]["mean_distance"], serde_json::Value::Null);
+        }
+    }
+
+    mod diversity {
+        use approx::assert_relative_eq;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        use crate::{
+            GeneticAlgorithm, crossover_method::UniformCrossover, diversity::Diversity,
+            genotype::Genotype, mutation_method::GaussianMutation,
+            selection_method::RouletteWheelSelection, stats::Stats, tests::TestIndividual,
+        };
+
+        fn individual(genes: &[f32]) -> TestIndividual {
+            TestIndividual::WithGenotype {
+                genotype: genes.iter().cloned().collect(),
+            }
+        }
+
+        #[test]
+        fn distances() {
+            let a: Genotype = [1.0, 2.0, 3.0].into_iter().collect();
+            let b: Genotype = [4.0, 6.0, 3.0].into_iter().collect();
+            let c: Genotype = [-2.0, -4.0, -6.0].into_iter().collect();
+
+            assert_relative_eq!(a.euclidean_distance(&b), 5.0);
+            assert_relative_eq!(a.manhattan_distance(&b), 7.0);
+            assert_relative_eq!(a.cosine_similarity(&c), -1.0);
+            assert_relative_eq!(a.cosine_similarity(&[0.0; 3].into_iter().collect()), 0.0);
+        }
+
+        #[test]
+        fn population_diversity() {
+            let population = vec![
+                individual(&[0.0, 1.0]),
+                individual(&[3.0, 1.0]),
+                individual(&[0.0, 5.0]),
+            ];
+
+            let diversity = Diversity::new(&population).unwrap();
+
+            // distances: 3, 4 and 5
+            assert_relative_eq!(diversity.mean_distance, 4.0);
+            assert_eq!(diversity.centroid, [1.0, 7.0 / 3.0].into_iter().collect());
+            assert_relative_eq!(
+                diversity.gene_variance.as_slice(),
+                [2.0, 32.0 / 9.0].as_ref()
+            );
+            assert_relative_eq!(diversity.mean_gene_variance(), (2.0 + 32.0 / 9.0) / 2.0);
+        }
+
+        #[test]
+        fn converged_population() {
+            let population = vec![individual(&[1.0, 2.0]); 4];
+
+            let diversity = Stats::new(&population)
+                .with_diversity(&population)
+                .diversity
+                .unwrap();
+
+            assert_relative_eq!(diversity.mean_distance, 0.0);
+            assert_relative_eq!(diversity.mean_gene_variance(), 0.0);
+        }
+
+        #[test]
+        fn is_opt_in() {
+            let population = vec![individual(&[1.0, 2.0]), individual(&[3.0, 1.0])];
+            let ga = || {
+                GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    GaussianMutation::new(0.5, 0.5),
+                )
+            };
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let (_, stats) = ga().evolve(&mut rng, &population);
+            assert!(stats.diversity.is_none());
+
+            let (_, stats) = ga().with_diversity(true).evolve(&mut rng, &population);
+            assert_relative_eq!(stats.diversity.unwrap().mean_distance, 5.0f32.sqrt());
+
+            // individuals without genotypes are fine as long as nobody asks
+            let stats = Stats::new(&[TestIndividual::new(1.0), TestIndividual::new(2.0)]);
+            assert!(stats.diversity.is_none());
+        }
+
+        #[test]
+        fn genotypes_of_different_lengths() {
+            let population = vec![individual(&[1.0, 2.0]), individual(&[1.0])];
+
+            assert!(Diversity::new(&population).is_none());
+        }
+    }
+
     mod mutation {
 
         mod gaussian_mutation {
@@ -308,5 +1300,147 @@ mod tests {
                 }
             }
         }
+
+        mod normal_mutation {
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            use crate::{
+                genotype::Genotype,
+                mutation_method::{MutationMethod, NormalMutation},
+            };
+
+            #[test]
+            fn follows_a_normal_distribution() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut child: Genotype = (0..10_000).map(|_| 0.0).collect();
+
+                NormalMutation::new(1.0, 0.5).mutate(&mut rng, &mut child);
+
+                let n = child.len() as f32;
+                let mean = child.iter().sum::<f32>() / n;
+                let std = (child.iter().map(|gene| (gene - mean).powi(2)).sum::<f32>() / n).sqrt();
+                let within_one_std = child.iter().filter(|gene| gene.abs() <= 0.5).count();
+                assert!(mean.abs() < 0.02);
+                assert!((std - 0.5).abs() < 0.02);
+                // about 68% for a normal distribution, 100% for a uniform one
+                assert!((6600..7000).contains(&within_one_std));
+            }
+
+            #[test]
+            fn zero_chance_does_not_change_the_genotype() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut child: Genotype = [1.0, 2.0, 3.0].into_iter().collect();
+
+                NormalMutation::new(0.0, 0.5).mutate(&mut rng, &mut child);
+
+                assert_eq!(child, [1.0, 2.0, 3.0].into_iter().collect());
+            }
+        }
+
+        mod polynomial_mutation {
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            use crate::{
+                genotype::Genotype,
+                mutation_method::{MutationMethod, PolynomialMutation},
+            };
+
+            #[test]
+            fn stays_within_bounds() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut child: Genotype = (0..1000).map(|i| i as f32 / 999.0 * 2.0 - 1.0).collect();
+                let original = child.clone();
+
+                PolynomialMutation::new(1.0, 20.0, -1.0, 1.0).mutate(&mut rng, &mut child);
+
+                assert!(child.iter().all(|gene| (-1.0..=1.0).contains(gene)));
+                assert_ne!(child, original);
+                // large `eta` keeps most changes small
+                let small = child
+                    .iter()
+                    .zip(original.iter())
+                    .filter(|(a, b)| (*a - *b).abs() < 0.2)
+                    .count();
+                assert!(small > 900);
+            }
+        }
+
+        mod one_fifth_rule_mutation {
+            use approx::assert_relative_eq;
+
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            use crate::{
+                GeneticAlgorithm,
+                crossover_method::UniformCrossover,
+                individual::Individual,
+                mutation_method::{MutationMethod, OneFifthRuleMutation},
+                selection_method::RouletteWheelSelection,
+                tests::TestIndividual,
+            };
+
+            #[test]
+            fn adapts_sigma_to_the_success_rate() {
+                let mut mutation = OneFifthRuleMutation::with_factor(1.0, 1.0, 0.5);
+
+                mutation.adapt(0.5);
+                assert_relative_eq!(mutation.sigma(), 2.0);
+                mutation.adapt(0.2);
+                assert_relative_eq!(mutation.sigma(), 2.0);
+                mutation.adapt(0.0);
+                mutation.adapt(0.1);
+                assert_relative_eq!(mutation.sigma(), 0.5);
+            }
+
+            #[test]
+            fn is_adapted_by_the_genetic_algorithm() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                // without any mutation, no child can beat its parents
+                let mut ga = GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    OneFifthRuleMutation::with_factor(0.0, 1.0, 0.5),
+                );
+                let mut population =
+                    vec![TestIndividual::create([1.0, 2.0].into_iter().collect()); 4];
+
+                for _ in 0..3 {
+                    (population, _) = ga.evolve(&mut rng, &population);
+                }
+
+                // adapted before the second and third generations
+                assert_relative_eq!(ga.mutation_method.sigma(), 0.25);
+            }
+        }
+
+        mod self_adaptive_mutation {
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            use crate::mutation_method::{MutationMethod, SelfAdaptiveMutation};
+
+            #[test]
+            fn mutates_genes_and_their_step_sizes() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut child = SelfAdaptiveMutation::attach_step_sizes([1.0, 2.0, 3.0], 0.1);
+                assert_eq!(child.len(), 6);
+
+                let mutation = SelfAdaptiveMutation::new(0.05);
+                for _ in 0..100 {
+                    mutation.mutate(&mut rng, &mut child);
+                }
+
+                assert_eq!(child.len(), 6);
+                assert!(
+                    SelfAdaptiveMutation::step_sizes(&child)
+                        .iter()
+                        .all(|&step| step >= 0.05 && step != 0.1)
+                );
+                assert_ne!(SelfAdaptiveMutation::genes(&child), [1.0, 2.0, 3.0]);
+            }
+        }
     }
 }
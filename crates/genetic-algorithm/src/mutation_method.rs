@@ -1,11 +1,21 @@
 use rand::{Rng, RngCore};
+use rand_distr::StandardNormal;
 
 use crate::genotype::Genotype;
 
 pub trait MutationMethod {
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Genotype);
+
+    /// Called by [`GeneticAlgorithm::evolve`](crate::GeneticAlgorithm::evolve)
+    /// at the start of every generation but the first, `success_rate` being
+    /// the share of the children bred in the previous one that turned out
+    /// fitter than their fittest parent; does nothing unless overridden
+    fn adapt(&mut self, _success_rate: f32) {}
 }
 
+/// Despite its name, changes genes by a uniform amount of up to `coeff`
+/// with a random sign; see [`NormalMutation`] for normally distributed
+/// changes
 #[derive(Debug, Clone)]
 pub struct GaussianMutation {
     /// Probability of changing a gene
@@ -39,3 +49,192 @@ impl MutationMethod for GaussianMutation {
     }
 }
 
+/// Adds normally distributed noise of standard deviation `sigma` to genes
+#[derive(Debug, Clone)]
+pub struct NormalMutation {
+    /// Probability of changing a gene
+    chance: f32,
+    sigma: f32,
+}
+
+impl NormalMutation {
+    pub fn new(chance: f32, sigma: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(sigma >= 0.0);
+
+        Self { chance, sigma }
+    }
+}
+
+impl MutationMethod for NormalMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Genotype) {
+        for gene in child.iter_mut() {
+            if rng.random_bool(self.chance as f64) {
+                *gene += self.sigma * rng.sample::<f32, _>(StandardNormal);
+            }
+        }
+    }
+}
+
+/// Polynomial mutation for genes bounded to `min..=max`: changes are
+/// proportional to the width of the range and the larger `eta`, the more
+/// likely they are small
+#[derive(Debug, Clone)]
+pub struct PolynomialMutation {
+    /// Probability of changing a gene
+    chance: f32,
+    eta: f32,
+    min: f32,
+    max: f32,
+}
+
+impl PolynomialMutation {
+    pub fn new(chance: f32, eta: f32, min: f32, max: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(eta >= 0.0);
+        assert!(min < max);
+
+        Self {
+            chance,
+            eta,
+            min,
+            max,
+        }
+    }
+}
+
+impl MutationMethod for PolynomialMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Genotype) {
+        let exponent = 1.0 / (self.eta + 1.0);
+        for gene in child.iter_mut() {
+            if !rng.random_bool(self.chance as f64) {
+                continue;
+            }
+
+            let u: f32 = rng.random();
+            let delta = if u < 0.5 {
+                (2.0 * u).powf(exponent) - 1.0
+            } else {
+                1.0 - (2.0 * (1.0 - u)).powf(exponent)
+            };
+            *gene = (*gene + delta * (self.max - self.min)).clamp(self.min, self.max);
+        }
+    }
+}
+
+/// [`NormalMutation`] whose `sigma` follows the 1/5th success rule: it
+/// grows while more than a fifth of the mutations are successful and
+/// shrinks otherwise, see [`MutationMethod::adapt`]
+#[derive(Debug, Clone)]
+pub struct OneFifthRuleMutation {
+    /// Probability of changing a gene
+    chance: f32,
+    sigma: f32,
+    /// Between 0 and 1, `sigma` is divided by it to grow and multiplied by
+    /// it to shrink
+    factor: f32,
+}
+
+impl OneFifthRuleMutation {
+    pub fn new(chance: f32, sigma: f32) -> Self {
+        Self::with_factor(chance, sigma, 0.85)
+    }
+
+    pub fn with_factor(chance: f32, sigma: f32, factor: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(sigma > 0.0);
+        assert!(factor > 0.0 && factor < 1.0);
+
+        Self {
+            chance,
+            sigma,
+            factor,
+        }
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+}
+
+impl MutationMethod for OneFifthRuleMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Genotype) {
+        NormalMutation::new(self.chance, self.sigma).mutate(rng, child);
+    }
+
+    fn adapt(&mut self, success_rate: f32) {
+        if success_rate > 0.2 {
+            self.sigma /= self.factor;
+        } else if success_rate < 0.2 {
+            self.sigma *= self.factor;
+        }
+    }
+}
+
+/// Self-adaptive mutation where every gene has its own step size, carried
+/// by the genotype and mutated along with it, so that steps shrink on
+/// their own as the population converges.
+///
+/// Genotypes are made of their genes followed by as many step sizes, see
+/// [`SelfAdaptiveMutation::attach_step_sizes`] and
+/// [`SelfAdaptiveMutation::genes`]; crossovers working gene by gene pass
+/// them on like any other gene.
+///
+/// Genotypes are thus twice as long as the genes they describe: individuals
+/// must decode them through [`SelfAdaptiveMutation::genes`], e.g. brains
+/// cannot be built from them directly, and crossovers relying on the
+/// layout of genes, like
+/// [`NeuronCrossover`](crate::crossover_method::NeuronCrossover), need
+/// groups covering the step sizes too.
+#[derive(Debug, Clone)]
+pub struct SelfAdaptiveMutation {
+    /// Lower bound of step sizes, so that they never collapse to zero
+    min_step: f32,
+}
+
+impl SelfAdaptiveMutation {
+    pub fn new(min_step: f32) -> Self {
+        assert!(min_step > 0.0);
+
+        Self { min_step }
+    }
+
+    /// `genes` followed by a step size of `step` for each of them
+    pub fn attach_step_sizes(genes: impl IntoIterator<Item = f32>, step: f32) -> Genotype {
+        let mut genes: Vec<f32> = genes.into_iter().collect();
+        genes.extend(vec![step; genes.len()]);
+        Genotype { genes }
+    }
+
+    /// Genes of a genotype, without their step sizes
+    pub fn genes(genotype: &Genotype) -> &[f32] {
+        &genotype.genes[..genotype.len() / 2]
+    }
+
+    pub fn step_sizes(genotype: &Genotype) -> &[f32] {
+        &genotype.genes[genotype.len() / 2..]
+    }
+}
+
+impl MutationMethod for SelfAdaptiveMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Genotype) {
+        assert!(
+            child.len().is_multiple_of(2),
+            "got a genotype without step sizes"
+        );
+
+        let half = child.len() / 2;
+        let n = half as f32;
+        // learning rates recommended by Schwefel
+        let global_rate = 1.0 / (2.0 * n).sqrt();
+        let local_rate = 1.0 / (2.0 * n.sqrt()).sqrt();
+        let global = global_rate * rng.sample::<f32, _>(StandardNormal);
+
+        let (genes, steps) = child.genes.split_at_mut(half);
+        for (gene, step) in genes.iter_mut().zip(steps) {
+            let local = local_rate * rng.sample::<f32, _>(StandardNormal);
+            *step = (*step * (global + local).exp()).max(self.min_step);
+            *gene += *step * rng.sample::<f32, _>(StandardNormal);
+        }
+    }
+}
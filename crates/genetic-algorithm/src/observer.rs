@@ -0,0 +1,34 @@
+use crate::{Genotype, stats::Stats};
+
+/// Callbacks invoked by
+/// [`GeneticAlgorithm::evolve`](crate::GeneticAlgorithm::evolve) at each
+/// stage of a generation, e.g. for logging or metrics; every one of them
+/// does nothing unless overridden.
+///
+/// Generations are counted from 0 by each genetic algorithm. Observers
+/// needing to keep state should use interior mutability, like `Cell`.
+pub trait Observer<I> {
+    fn on_generation_start(&self, _generation: usize, _population: &[I]) {}
+
+    /// Fittest individual so far, fitter than those of all previous
+    /// generations
+    fn on_new_best(&self, _generation: usize, _individual: &I) {}
+
+    fn on_parents_selected(&self, _parent_a: &I, _parent_b: &I) {}
+
+    /// Child of the parents, before it is mutated; not invoked by
+    /// [`GeneticAlgorithm::evolve_with`](crate::GeneticAlgorithm::evolve_with)
+    fn on_crossover(&self, _child: &Genotype) {}
+
+    /// Not invoked by
+    /// [`GeneticAlgorithm::evolve_with`](crate::GeneticAlgorithm::evolve_with)
+    fn on_mutation(&self, _child: &Genotype) {}
+
+    fn on_generation_end(&self, _generation: usize, _offspring: &[I], _stats: &Stats) {}
+}
+
+/// Observer doing nothing, the default one
+#[derive(Debug, Clone, Default)]
+pub struct NoObserver;
+
+impl<I> Observer<I> for NoObserver {}
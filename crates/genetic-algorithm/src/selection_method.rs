@@ -1,12 +1,32 @@
 use crate::individual::Individual;
-use rand::{RngCore, seq::IndexedRandom};
+use rand::{
+    Rng, RngCore,
+    distr::{Distribution, weighted::WeightedIndex},
+    seq::IndexedRandom,
+};
 
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
         I: Individual;
+
+    /// `count` individuals selected at once; independent calls to
+    /// [`SelectionMethod::select`] unless a method can do better
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        (0..count).map(|_| self.select(rng, population)).collect()
+    }
 }
 
+/// Chance of being selected proportional to fitness, uniform when every
+/// fitness is zero; fitness must not be negative
 #[derive(Debug, Clone)]
 pub struct RouletteWheelSelection;
 
@@ -15,8 +35,224 @@ impl SelectionMethod for RouletteWheelSelection {
     where
         I: Individual,
     {
+        assert_non_negative(population);
+
+        if population
+            .iter()
+            .all(|individual| individual.fitness() == 0.0)
+        {
+            return population.choose(rng).expect("got an empty population!");
+        }
+
         population
             .choose_weighted(rng, |individual| individual.fitness())
             .expect("got an empty population!")
     }
 }
+
+/// Fittest of `size` individuals drawn uniformly, with replacement; larger
+/// tournaments mean a stronger selection pressure
+#[derive(Debug, Clone)]
+pub struct TournamentSelection {
+    pub size: usize,
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        assert!(self.size > 0);
+
+        (0..self.size)
+            .map(|_| population.choose(rng).expect("got an empty population!"))
+            .reduce(|best, individual| {
+                if individual.fitness() > best.fitness() {
+                    individual
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+}
+
+/// How [`RankSelection`] weighs the ranks, the worst individual having
+/// rank 0 and the best `n - 1`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RankPressure {
+    /// Weights growing linearly with the rank, the best individual being
+    /// this many times more likely than average to be selected, from 1.0
+    /// (uniform) to 2.0 (the worst is never selected)
+    Linear(f32),
+    /// Weight `base ^ (n - 1 - rank)`, with `base` between 0 and 1;
+    /// the smaller it is, the stronger the pressure
+    Exponential(f32),
+}
+
+/// Chance of being selected depending on the rank by fitness rather than
+/// the fitness itself, so that outliers do not take over the population
+#[derive(Debug, Clone)]
+pub struct RankSelection {
+    pub pressure: RankPressure,
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        let ranked = ranked(population);
+        let n = ranked.len() as f32;
+
+        let weight = |rank: usize| match self.pressure {
+            RankPressure::Linear(_) if ranked.len() == 1 => 1.0,
+            RankPressure::Linear(s) => {
+                assert!((1.0..=2.0).contains(&s));
+                (2.0 - s) / n + 2.0 * rank as f32 * (s - 1.0) / (n * (n - 1.0))
+            }
+            RankPressure::Exponential(base) => {
+                assert!(base > 0.0 && base < 1.0);
+                base.powi((ranked.len() - 1 - rank) as i32)
+            }
+        };
+
+        let ranks =
+            WeightedIndex::new((0..ranked.len()).map(weight)).expect("got an empty population!");
+        (0..count).map(|_| ranked[ranks.sample(rng)]).collect()
+    }
+}
+
+/// Roulette wheel spun once with evenly spaced pointers, so that
+/// [`SelectionMethod::select_many`] picks every individual close to its
+/// expected number of times, in the order of the population; single
+/// selections behave like [`RouletteWheelSelection`]
+#[derive(Debug, Clone)]
+pub struct StochasticUniversalSampling;
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        assert!(!population.is_empty(), "got an empty population!");
+        assert_non_negative(population);
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let total: f32 = population.iter().map(Individual::fitness).sum();
+        if total == 0.0 {
+            return (0..count)
+                .map(|_| population.choose(rng).unwrap())
+                .collect();
+        }
+
+        let spacing = total / count as f32;
+        let start = rng.random_range(0.0..spacing);
+
+        let mut selected = Vec::with_capacity(count);
+        // individuals without fitness take up no room on the wheel, and a
+        // pointer on the boundary of two individuals selects the latter
+        let mut individuals = population
+            .iter()
+            .filter(|individual| individual.fitness() > 0.0);
+        let mut individual = individuals.next().unwrap();
+        let mut cumulative = individual.fitness();
+        for pointer in (0..count).map(|i| start + i as f32 * spacing) {
+            while cumulative <= pointer {
+                match individuals.next() {
+                    Some(next) => {
+                        individual = next;
+                        cumulative += individual.fitness();
+                    }
+                    // rounding errors on the last pointer
+                    None => break,
+                }
+            }
+            selected.push(individual);
+        }
+        selected
+    }
+}
+
+/// Uniform choice among the fittest `fraction` of the population, at least
+/// one individual
+#[derive(Debug, Clone)]
+pub struct TruncationSelection {
+    pub fraction: f32,
+}
+
+impl SelectionMethod for TruncationSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        assert!(self.fraction > 0.0 && self.fraction <= 1.0);
+
+        let ranked = ranked(population);
+        let fittest = ((ranked.len() as f32 * self.fraction).ceil() as usize).max(1);
+        let fittest = &ranked[ranked.len().saturating_sub(fittest)..];
+        (0..count)
+            .map(|_| *fittest.choose(rng).expect("got an empty population!"))
+            .collect()
+    }
+}
+
+fn assert_non_negative<I>(population: &[I])
+where
+    I: Individual,
+{
+    assert!(
+        population
+            .iter()
+            .all(|individual| individual.fitness() >= 0.0),
+        "fitness must not be negative for fitness proportionate selection"
+    );
+}
+
+/// Population sorted from the least to the most fit
+fn ranked<I>(population: &[I]) -> Vec<&I>
+where
+    I: Individual,
+{
+    let mut ranked: Vec<&I> = population.iter().collect();
+    ranked.sort_by(|a, b| a.fitness().total_cmp(&b.fitness()));
+    ranked
+}
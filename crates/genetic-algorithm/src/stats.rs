@@ -1,10 +1,43 @@
-use crate::Individual;
+use crate::{Individual, diversity::Diversity};
 
-#[derive(Debug, Clone)]
+/// Number of bins of [`Stats::histogram`]
+pub const HISTOGRAM_BINS: usize = 10;
+
+/// Which population [`Stats`] returned by
+/// [`GeneticAlgorithm::evolve`](crate::GeneticAlgorithm::evolve) describe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsPopulation {
+    /// The population that was evolved, with the fitness it earned
+    #[default]
+    Parents,
+    /// The new population, only meaningful when individuals know their
+    /// fitness as soon as they are created
+    Offspring,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Stats {
     pub avg_fitness: f32,
     pub min_fitness: f32,
     pub max_fitness: f32,
+    pub median_fitness: f32,
+    /// Population standard deviation of the fitness
+    pub std_fitness: f32,
+    /// First and third quartiles of the fitness
+    pub quartiles: (f32, f32),
+    /// Number of individuals per fitness range, [`HISTOGRAM_BINS`] ranges
+    /// of equal width from `min_fitness` to `max_fitness`
+    pub histogram: Vec<usize>,
+    /// Index of the fittest individual, the first one on ties
+    pub best_index: usize,
+    /// Generation the stats were taken from, as counted by
+    /// [`GeneticAlgorithm::evolve`](crate::GeneticAlgorithm::evolve); left at
+    /// 0 by [`Stats::new`], see [`Stats::with_generation`]
+    pub generation: usize,
+    pub population: StatsPopulation,
+    /// `None` unless asked for with [`Stats::with_diversity`], or when the
+    /// genotypes of the population differ in length
+    pub diversity: Option<Diversity>,
 }
 
 impl Stats {
@@ -14,22 +47,85 @@ impl Stats {
     {
         assert!(!population.is_empty());
 
-        let mut min_fitness = population[0].fitness();
+        let fitnesses: Vec<f32> = population.iter().map(Individual::fitness).collect();
+        let n = fitnesses.len() as f32;
+
+        let mut min_fitness = fitnesses[0];
         let mut max_fitness = min_fitness;
+        let mut best_index = 0;
         let mut sum_fitness = 0.0;
 
-        for individual in population {
-            let fitness = individual.fitness();
-
+        for (index, &fitness) in fitnesses.iter().enumerate() {
             min_fitness = min_fitness.min(fitness);
-            max_fitness = max_fitness.max(fitness);
+            if fitness > max_fitness {
+                max_fitness = fitness;
+                best_index = index;
+            }
             sum_fitness += fitness;
         }
 
+        let avg_fitness = sum_fitness / n;
+        let std_fitness = (fitnesses
+            .iter()
+            .map(|fitness| (fitness - avg_fitness).powi(2))
+            .sum::<f32>()
+            / n)
+            .sqrt();
+
+        let mut sorted = fitnesses.clone();
+        sorted.sort_by(f32::total_cmp);
+
         Self {
             min_fitness,
             max_fitness,
-            avg_fitness: sum_fitness / (population.len() as f32),
+            avg_fitness,
+            median_fitness: percentile(&sorted, 0.5),
+            std_fitness,
+            quartiles: (percentile(&sorted, 0.25), percentile(&sorted, 0.75)),
+            histogram: histogram(&fitnesses, min_fitness, max_fitness),
+            best_index,
+            generation: 0,
+            population: StatsPopulation::Parents,
+            diversity: None,
         }
     }
+
+    pub fn with_generation(mut self, generation: usize) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Adds the [`Diversity`] of `population`, the one the stats were taken
+    /// from; takes time quadratic in its size and needs genotypes.
+    pub fn with_diversity<I>(mut self, population: &[I]) -> Self
+    where
+        I: Individual,
+    {
+        self.diversity = Diversity::new(population);
+        self
+    }
+}
+
+/// Value below which `p` (from 0 to 1) of the `sorted` values fall,
+/// interpolated linearly between the closest ones
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let position = p * (sorted.len() - 1) as f32;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f32)
+}
+
+fn histogram(fitnesses: &[f32], min: f32, max: f32) -> Vec<usize> {
+    let mut histogram = vec![0; HISTOGRAM_BINS];
+    let width = (max - min) / HISTOGRAM_BINS as f32;
+
+    for &fitness in fitnesses {
+        let bin = if width > 0.0 {
+            (((fitness - min) / width) as usize).min(HISTOGRAM_BINS - 1)
+        } else {
+            0
+        };
+        histogram[bin] += 1;
+    }
+    histogram
 }
@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+use crate::{history::History, stats::Stats};
+
+/// When to stop evolving, see
+/// [`GeneticAlgorithm::run_until`](crate::GeneticAlgorithm::run_until);
+/// several criteria stop the run as soon as any of them is met
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    /// That many generations were evaluated; none for 0
+    MaxGenerations(usize),
+    /// Some individual reached that fitness
+    TargetFitness(f32),
+    /// The maximum fitness did not improve by more than `tolerance` for
+    /// that many generations
+    Stagnation { generations: usize, tolerance: f32 },
+    /// The run took that long
+    TimeLimit(Duration),
+    /// The mean distance between genotypes fell below that value, see
+    /// [`Diversity`](crate::diversity::Diversity)
+    DiversityCollapse(f32),
+}
+
+impl Criterion {
+    /// Whether the criterion is met after the generations of `history`,
+    /// evolved in `elapsed`; only [`Criterion::MaxGenerations`] can be met
+    /// before the first generation
+    pub fn is_met(&self, history: &History, elapsed: Duration) -> bool {
+        let last = history.last();
+
+        match *self {
+            Self::MaxGenerations(generations) => history.len() >= generations,
+            Self::TargetFitness(fitness) => last.is_some_and(|last| last.max_fitness >= fitness),
+            Self::Stagnation {
+                generations,
+                tolerance,
+            } => last.is_some() && history.is_plateau(generations, tolerance),
+            Self::TimeLimit(limit) => last.is_some() && elapsed >= limit,
+            Self::DiversityCollapse(min_distance) => last
+                .and_then(|last| last.diversity.as_ref())
+                .is_some_and(|diversity| diversity.mean_distance < min_distance),
+        }
+    }
+}
+
+/// First of `criteria` met, if any
+pub fn first_met(
+    criteria: &[Criterion],
+    history: &History,
+    elapsed: Duration,
+) -> Option<Criterion> {
+    criteria
+        .iter()
+        .find(|criterion| criterion.is_met(history, elapsed))
+        .copied()
+}
+
+/// Records the stats returned by `step`, one generation per call, until any
+/// of `criteria` is met, which is checked before every step; returns that
+/// criterion and the stats recorded
+pub fn record_until(
+    criteria: &[Criterion],
+    mut step: impl FnMut() -> Stats,
+) -> (Criterion, History) {
+    assert!(!criteria.is_empty());
+
+    let start = Instant::now();
+    let mut history = History::new();
+    loop {
+        if let Some(reason) = first_met(criteria, &history, start.elapsed()) {
+            return (reason, history);
+        }
+        history.push(step());
+    }
+}
+
+/// Outcome of [`GeneticAlgorithm::run_until`](crate::GeneticAlgorithm::run_until)
+#[derive(Debug, Clone)]
+pub struct Run<I> {
+    /// Last population evaluated, the one the final stats describe, or the
+    /// initial one if no generation ran
+    pub population: Vec<I>,
+    pub history: History,
+    /// Criterion that stopped the run
+    pub reason: Criterion,
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+/// Non-linearity applied to every neuron output of a layer
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Activation {
+    /// `max(0, x)`
+    #[default]
+    Relu,
+    /// `x` for positive inputs, `alpha * x` otherwise
+    LeakyRelu { alpha: f32 },
+    /// `1 / (1 + e^-x)`, output in (0, 1)
+    Sigmoid,
+    /// Hyperbolic tangent, output in (-1, 1)
+    Tanh,
+    /// Passes the weighted sum through unchanged
+    Identity,
+    /// `x / (1 + |x|)`, output in (-1, 1)
+    Softsign,
+}
+
+impl Activation {
+    pub fn apply(&self, x: f32) -> f32 {
+        match *self {
+            Self::Relu => x.max(0.0),
+            Self::LeakyRelu { alpha } => {
+                if x > 0.0 {
+                    x
+                } else {
+                    alpha * x
+                }
+            }
+            Self::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Self::Tanh => x.tanh(),
+            Self::Identity => x,
+            Self::Softsign => x / (1.0 + x.abs()),
+        }
+    }
+
+    /// Slope of [`Activation::apply`] at `x`
+    pub fn derivative(&self, x: f32) -> f32 {
+        match *self {
+            Self::Relu => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::LeakyRelu { alpha } => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    alpha
+                }
+            }
+            Self::Sigmoid => {
+                let y = self.apply(x);
+                y * (1.0 - y)
+            }
+            Self::Tanh => 1.0 - x.tanh().powi(2),
+            Self::Identity => 1.0,
+            Self::Softsign => 1.0 / (1.0 + x.abs()).powi(2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn apply() {
+        assert_relative_eq!(Activation::Relu.apply(-2.0), 0.0);
+        assert_relative_eq!(Activation::Relu.apply(2.0), 2.0);
+        assert_relative_eq!(Activation::LeakyRelu { alpha: 0.1 }.apply(-2.0), -0.2);
+        assert_relative_eq!(Activation::LeakyRelu { alpha: 0.1 }.apply(2.0), 2.0);
+        assert_relative_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+        assert_relative_eq!(Activation::Tanh.apply(-1.0), -0.7615942);
+        assert_relative_eq!(Activation::Identity.apply(-3.5), -3.5);
+        assert_relative_eq!(Activation::Softsign.apply(-1.0), -0.5);
+    }
+
+    #[test]
+    fn derivative_matches_finite_differences() {
+        let activations = [
+            Activation::Relu,
+            Activation::LeakyRelu { alpha: 0.1 },
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Identity,
+            Activation::Softsign,
+        ];
+        let h = 1e-3;
+
+        for activation in activations {
+            for x in [-1.5, -0.3, 0.4, 2.0] {
+                let slope = (activation.apply(x + h) - activation.apply(x - h)) / (2.0 * h);
+                assert_relative_eq!(activation.derivative(x), slope, epsilon = 1e-2);
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::mem;
+
+use crate::{
+    Activation, LayerKind, LayerTopology, Network, Scratch, State,
+    kernel::{self, Params},
+};
+
+/// Many networks sharing one topology, evaluated together.
+///
+/// Parameters are stored structure-of-arrays: every weight is followed by
+/// the same weight of all the other networks, so the inner loops run over
+/// contiguous memory and can be auto-vectorised.
+#[derive(Debug, Clone)]
+pub struct NetworkBatch {
+    len: usize,
+    topology: Vec<LayerTopology>,
+    layers: Vec<BatchLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct BatchLayer {
+    input_size: usize,
+    output_size: usize,
+    kind: LayerKind,
+    activation: Activation,
+    /// `[row][input][network]`
+    weights: Vec<f32>,
+    /// `[row][output][network]`
+    recurrent: Vec<f32>,
+    /// `[row][network]`
+    biases: Vec<f32>,
+}
+
+impl BatchLayer {
+    fn params(&self) -> Params<'_> {
+        Params {
+            kind: self.kind,
+            activation: self.activation,
+            input_size: self.input_size,
+            output_size: self.output_size,
+            weights: &self.weights,
+            recurrent: &self.recurrent,
+            biases: &self.biases,
+        }
+    }
+}
+
+/// Interleaves the same parameter of every network, `values` picks the
+/// parameters of one layer out of a network.
+fn interleave<'a>(networks: &[&'a Network], values: impl Fn(&'a Network) -> &'a [f32]) -> Vec<f32> {
+    let size = values(networks[0]).len();
+    (0..size)
+        .flat_map(|index| networks.iter().map(move |network| (network, index)))
+        .map(|(network, index)| values(network)[index])
+        .collect()
+}
+
+impl NetworkBatch {
+    pub fn new<'a>(networks: impl IntoIterator<Item = &'a Network>) -> Self {
+        let networks: Vec<&Network> = networks.into_iter().collect();
+        assert!(!networks.is_empty(), "got an empty batch");
+
+        let topology = networks[0].topology();
+        assert!(
+            networks
+                .iter()
+                .all(|network| network.topology() == topology),
+            "got networks with different topologies"
+        );
+
+        let layers = networks[0]
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| BatchLayer {
+                input_size: layer.input_size,
+                output_size: layer.output_size,
+                kind: layer.kind,
+                activation: layer.activation,
+                weights: interleave(&networks, |n| &n.layers[index].weights),
+                recurrent: interleave(&networks, |n| &n.layers[index].recurrent),
+                biases: interleave(&networks, |n| &n.layers[index].biases),
+            })
+            .collect();
+
+        Self {
+            len: networks.len(),
+            topology,
+            layers,
+        }
+    }
+
+    /// Number of networks in the batch
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn topology(&self) -> &[LayerTopology] {
+        &self.topology
+    }
+
+    /// Fresh memory for the recurrent layers of every network in the batch
+    pub fn state(&self) -> State {
+        State::new(self.layers.iter().map(|layer| {
+            if layer.kind.is_recurrent() {
+                layer.output_size * self.len
+            } else {
+                0
+            }
+        }))
+    }
+
+    /// Propagates `inputs` holding one row of inputs per network, back to
+    /// back, and returns one row of outputs per network in the same order.
+    ///
+    /// Recurrent layers behave as if their previous outputs were all zero.
+    pub fn propagate_into<'s>(&self, inputs: &[f32], scratch: &'s mut Scratch) -> &'s [f32] {
+        self.run(inputs, None, scratch)
+    }
+
+    /// Like [`NetworkBatch::propagate_into`], but recurrent layers see and
+    /// update their previous outputs kept in `state`.
+    pub fn propagate_with_state<'s>(
+        &self,
+        inputs: &[f32],
+        state: &mut State,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        assert_eq!(state.hidden.len(), self.layers.len());
+
+        self.run(inputs, Some(state), scratch)
+    }
+
+    fn run<'s>(
+        &self,
+        inputs: &[f32],
+        mut state: Option<&mut State>,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        let n = self.len;
+        let input_size = self.topology[0].neurons;
+        assert_eq!(inputs.len(), n * input_size);
+
+        let Scratch {
+            current,
+            next,
+            gates,
+            ..
+        } = scratch;
+
+        current.clear();
+        current
+            .extend((0..input_size).flat_map(|j| (0..n).map(move |k| inputs[k * input_size + j])));
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let hidden = match &mut state {
+                Some(state) if layer.kind.is_recurrent() => Some(&mut state.hidden[index]),
+                _ => None,
+            };
+
+            kernel::forward(
+                layer.params(),
+                n,
+                current,
+                hidden.as_deref().map(Vec::as_slice),
+                next,
+                gates,
+            );
+            if let Some(hidden) = hidden {
+                hidden.copy_from_slice(next);
+            }
+            mem::swap(current, next);
+        }
+
+        let output_size = self
+            .layers
+            .last()
+            .map_or(input_size, |layer| layer.output_size);
+        let outputs: &[f32] = current;
+        next.clear();
+        next.extend((0..n).flat_map(|k| (0..output_size).map(move |i| outputs[i * n + k])));
+
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn networks(rng: &mut ChaCha8Rng, hidden: LayerKind) -> Vec<Network> {
+        let topology = [
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Relu,
+                kind: hidden,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+            },
+        ];
+        (0..5)
+            .map(|_| Network::random(&mut *rng, &topology))
+            .collect()
+    }
+
+    #[test]
+    fn propagate_into_matches_individual_networks() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let networks = networks(&mut rng, LayerKind::Dense);
+        let inputs: Vec<f32> = (0..15).map(|i| (i as f32 - 7.0) / 7.0).collect();
+
+        let batch = NetworkBatch::new(&networks);
+        let mut scratch = Scratch::new();
+        let actual = batch.propagate_into(&inputs, &mut scratch);
+
+        let expected: Vec<f32> = networks
+            .iter()
+            .zip(inputs.chunks_exact(3))
+            .flat_map(|(network, inputs)| network.propagate(inputs.to_vec()))
+            .collect();
+
+        approx::assert_relative_eq!(actual, expected.as_slice(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn propagate_with_state_matches_individual_networks() {
+        for kind in [LayerKind::Elman, LayerKind::Gru] {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let networks = networks(&mut rng, kind);
+            let batch = NetworkBatch::new(&networks);
+
+            let mut batch_state = batch.state();
+            let mut states: Vec<State> = networks.iter().map(Network::state).collect();
+            let (mut batch_scratch, mut scratch) = (Scratch::new(), Scratch::new());
+
+            for step in 0..3 {
+                let inputs: Vec<f32> = (0..15).map(|i| ((i + step) as f32).sin()).collect();
+                let actual =
+                    batch.propagate_with_state(&inputs, &mut batch_state, &mut batch_scratch);
+
+                let expected: Vec<f32> = networks
+                    .iter()
+                    .zip(&mut states)
+                    .zip(inputs.chunks_exact(3))
+                    .flat_map(|((network, state), inputs)| {
+                        network
+                            .propagate_with_state(inputs, state, &mut scratch)
+                            .to_vec()
+                    })
+                    .collect();
+
+                approx::assert_relative_eq!(actual, expected.as_slice(), epsilon = 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "different topologies")]
+    fn rejects_mixed_topologies() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let a = Network::random(
+            &mut rng,
+            &[2, 1].map(|neurons| LayerTopology {
+                neurons,
+                activation: Activation::Relu,
+                kind: LayerKind::Dense,
+            }),
+        );
+        let b = Network::random(
+            &mut rng,
+            &[3, 1].map(|neurons| LayerTopology {
+                neurons,
+                activation: Activation::Relu,
+                kind: LayerKind::Dense,
+            }),
+        );
+
+        NetworkBatch::new([&a, &b]);
+    }
+}
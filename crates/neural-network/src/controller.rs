@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::{Ctrnn, Error, NeatGenome, Network, Scratch, State};
+
+/// Anything that turns observations into actions and can be evolved as a
+/// flat list of genes.
+///
+/// Controllers own their memory, so every instance drives exactly one
+/// agent. Implementations without genes, like hand-written policies, return
+/// no genes and ignore them when rebuilt.
+pub trait Controller: fmt::Debug {
+    fn inputs(&self) -> usize;
+
+    fn outputs(&self) -> usize;
+
+    /// Produces the outputs for one time step, updating any memory.
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32];
+
+    /// Forgets everything seen so far.
+    fn reset(&mut self) {}
+
+    fn to_genes(&self) -> Vec<f32>;
+
+    /// Controller of the same shape with its parameters taken from `genes`
+    /// and fresh memory, or an error when `genes` does not fit that shape.
+    fn with_genes(&self, genes: &[f32]) -> Result<Box<dyn Controller>, Error>;
+
+    /// Layered network behind this controller, if any, so that callers can
+    /// evaluate many of them at once with a
+    /// [`NetworkBatch`](crate::NetworkBatch).
+    fn network(&self) -> Option<&Network> {
+        None
+    }
+
+    /// Genome behind this controller, if its topology evolves, so that
+    /// callers can breed it with NEAT operators instead of through its
+    /// flat genes.
+    fn neat(&self) -> Option<&NeatGenome> {
+        None
+    }
+}
+
+/// [`Network`] together with the memory of its recurrent layers
+#[derive(Debug, Clone)]
+pub struct NetworkController {
+    network: Network,
+    state: State,
+    scratch: Scratch,
+}
+
+impl NetworkController {
+    pub fn new(network: Network) -> Self {
+        Self {
+            state: network.state(),
+            scratch: Scratch::for_network(&network),
+            network,
+        }
+    }
+}
+
+impl Controller for NetworkController {
+    fn inputs(&self) -> usize {
+        self.network.input_size()
+    }
+
+    fn outputs(&self) -> usize {
+        self.network.output_size()
+    }
+
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        self.network
+            .propagate_with_state(inputs, &mut self.state, &mut self.scratch)
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    fn to_genes(&self) -> Vec<f32> {
+        self.network.weights().collect()
+    }
+
+    fn with_genes(&self, genes: &[f32]) -> Result<Box<dyn Controller>, Error> {
+        let network = Network::try_from_weights(&self.network.topology(), genes.iter().copied())?;
+        Ok(Box::new(Self::new(network)))
+    }
+
+    fn network(&self) -> Option<&Network> {
+        Some(&self.network)
+    }
+}
+
+/// [`Ctrnn`] together with its neuron states
+#[derive(Debug, Clone)]
+pub struct CtrnnController {
+    ctrnn: Ctrnn,
+    state: State,
+    scratch: Scratch,
+}
+
+impl CtrnnController {
+    pub fn new(ctrnn: Ctrnn) -> Self {
+        Self {
+            state: ctrnn.state(),
+            scratch: Scratch::new(),
+            ctrnn,
+        }
+    }
+}
+
+impl Controller for CtrnnController {
+    fn inputs(&self) -> usize {
+        self.ctrnn.topology.inputs
+    }
+
+    fn outputs(&self) -> usize {
+        self.ctrnn.topology.outputs
+    }
+
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        self.ctrnn
+            .propagate_with_state(inputs, &mut self.state, &mut self.scratch)
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    fn to_genes(&self) -> Vec<f32> {
+        self.ctrnn.weights().collect()
+    }
+
+    fn with_genes(&self, genes: &[f32]) -> Result<Box<dyn Controller>, Error> {
+        let ctrnn = Ctrnn::try_from_weights(&self.ctrnn.topology, genes.iter().copied())?;
+        Ok(Box::new(Self::new(ctrnn)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Activation, LayerKind, LayerTopology};
+
+    use super::*;
+
+    #[test]
+    fn network_controller_remembers_until_reset() {
+        let topology = [
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Elman,
+            },
+        ];
+        let mut controller =
+            NetworkController::new(Network::from_weights(&topology, [0.0, 1.0, 0.5]));
+
+        assert_eq!((controller.inputs(), controller.outputs()), (1, 1));
+        approx::assert_relative_eq!(controller.propagate(&[1.0])[0], 1.0);
+        approx::assert_relative_eq!(controller.propagate(&[0.0])[0], 0.5);
+
+        controller.reset();
+        approx::assert_relative_eq!(controller.propagate(&[0.0])[0], 0.0);
+    }
+
+    #[test]
+    fn with_genes_keeps_shape() {
+        let topology = [
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+        ];
+        let controller = NetworkController::new(Network::from_weights(&topology, [0.0; 3]));
+
+        let mut rebuilt = controller.with_genes(&[1.0, 2.0, 3.0]).unwrap();
+
+        assert_eq!(rebuilt.to_genes(), vec![1.0, 2.0, 3.0]);
+        approx::assert_relative_eq!(rebuilt.propagate(&[1.0, 1.0])[0], 6.0);
+        assert!(rebuilt.network().is_some());
+        assert_eq!(
+            controller.with_genes(&[1.0]).unwrap_err(),
+            Error::WeightCountMismatch {
+                expected: 3,
+                actual: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::iter::once;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{Activation, Error, Scratch, State};
+
+/// Shape and integration settings of a [`Ctrnn`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CtrnnTopology {
+    pub inputs: usize,
+    /// Number of fully interconnected neurons, the last `outputs` of them
+    /// are read as the outputs of the network
+    pub neurons: usize,
+    pub outputs: usize,
+    /// Turns a neuron state into its firing rate
+    pub activation: Activation,
+    /// Euler integration step, in the same unit as the time constants
+    pub step: f32,
+}
+
+/// Continuous-time recurrent neural network.
+///
+/// Every neuron `i` follows
+/// `τ_i · dy_i/dt = -y_i + Σ_j w_ij · σ(g_j · (y_j + θ_j)) + Σ_k v_ik · x_k`,
+/// integrated with one Euler step per propagation.
+#[derive(Debug, Clone)]
+pub struct Ctrnn {
+    pub(crate) topology: CtrnnTopology,
+    pub(crate) time_constants: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+    pub(crate) gains: Vec<f32>,
+    /// `[neuron][input]`
+    pub(crate) input_weights: Vec<f32>,
+    /// `[neuron][neuron]`, row `i` holds the weights into neuron `i`
+    pub(crate) weights: Vec<f32>,
+}
+
+impl Ctrnn {
+    /// Time constants are drawn from `[1, 2]`, gains from `[0.5, 1.5]` and
+    /// everything else from `[-1, 1]`.
+    pub fn random(rng: &mut dyn RngCore, topology: &CtrnnTopology) -> Self {
+        let mut params = Vec::with_capacity(Self::parameter_count(topology));
+
+        for _ in 0..topology.neurons {
+            params.push(rng.random_range(1.0..=2.0));
+            params.push(rng.random_range(-1.0..=1.0));
+            params.push(rng.random_range(0.5..=1.5));
+            params.extend(
+                (0..topology.inputs + topology.neurons).map(|_| rng.random_range(-1.0..=1.0)),
+            );
+        }
+
+        Self::from_weights(topology, params)
+    }
+
+    /// Expects, for every neuron: its time constant, bias, gain, input
+    /// weights and the weights coming from every neuron.
+    pub fn from_weights(topology: &CtrnnTopology, weights: impl IntoIterator<Item = f32>) -> Self {
+        Self::try_from_weights(topology, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_from_weights(
+        topology: &CtrnnTopology,
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, Error> {
+        if topology.neurons == 0
+            || topology.outputs > topology.neurons
+            || topology.step.is_nan()
+            || topology.step <= 0.0
+        {
+            return Err(Error::InvalidCtrnnTopology);
+        }
+
+        let weights: Vec<f32> = weights.into_iter().collect();
+        let expected = Self::try_parameter_count(topology)?;
+        if weights.len() != expected {
+            return Err(Error::WeightCountMismatch {
+                expected,
+                actual: weights.len(),
+            });
+        }
+
+        let mut weights = weights.into_iter();
+        let mut next = || weights.next().unwrap();
+
+        let n = topology.neurons;
+        let mut network = Self {
+            topology: *topology,
+            time_constants: Vec::with_capacity(n),
+            biases: Vec::with_capacity(n),
+            gains: Vec::with_capacity(n),
+            input_weights: Vec::with_capacity(n * topology.inputs),
+            weights: Vec::with_capacity(n * n),
+        };
+
+        for _ in 0..n {
+            network.time_constants.push(next());
+            network.biases.push(next());
+            network.gains.push(next());
+            network
+                .input_weights
+                .extend((0..topology.inputs).map(|_| next()));
+            network.weights.extend((0..n).map(|_| next()));
+        }
+
+        Ok(network)
+    }
+
+    /// Number of weights a network of this topology is built from
+    pub fn parameter_count(topology: &CtrnnTopology) -> usize {
+        Self::try_parameter_count(topology).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with [`Error::InvalidCtrnnTopology`] instead of overflowing
+    pub fn try_parameter_count(topology: &CtrnnTopology) -> Result<usize, Error> {
+        topology
+            .inputs
+            .checked_add(topology.neurons)
+            .and_then(|weights| weights.checked_add(3))
+            .and_then(|row| row.checked_mul(topology.neurons))
+            .ok_or(Error::InvalidCtrnnTopology)
+    }
+
+    pub fn topology(&self) -> &CtrnnTopology {
+        &self.topology
+    }
+
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        let (inputs, n) = (self.topology.inputs, self.topology.neurons);
+
+        (0..n).flat_map(move |i| {
+            once(self.time_constants[i])
+                .chain(once(self.biases[i]))
+                .chain(once(self.gains[i]))
+                .chain(self.input_weights[i * inputs..][..inputs].iter().copied())
+                .chain(self.weights[i * n..][..n].iter().copied())
+        })
+    }
+
+    /// Neuron states, all starting at rest
+    pub fn state(&self) -> State {
+        State::new(once(self.topology.neurons))
+    }
+
+    /// Single step starting from rest, see [`Ctrnn::propagate_with_state`]
+    /// to keep integrating over time.
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.propagate_with_state(&inputs, &mut self.state(), &mut Scratch::new())
+            .to_vec()
+    }
+
+    /// Advances the neuron states in `state` by one Euler step and returns
+    /// the firing rates of the output neurons.
+    pub fn propagate_with_state<'s>(
+        &self,
+        inputs: &[f32],
+        state: &mut State,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        let (n, topology) = (self.topology.neurons, &self.topology);
+        assert_eq!(inputs.len(), topology.inputs);
+
+        let states = &mut state.hidden[0];
+        assert_eq!(states.len(), n);
+
+        let rates = &mut scratch.current;
+        rates.clear();
+        rates.extend((0..n).map(|j| self.rate(j, states[j])));
+
+        for (i, state) in states.iter_mut().enumerate() {
+            let recurrent = dot(&self.weights[i * n..][..n], rates);
+            let external = dot(
+                &self.input_weights[i * topology.inputs..][..topology.inputs],
+                inputs,
+            );
+            // a non-positive time constant would make the state diverge
+            let time_constant = self.time_constants[i].abs().max(topology.step);
+
+            *state += topology.step * (recurrent + external - *state) / time_constant;
+        }
+
+        let outputs = &mut scratch.next;
+        outputs.clear();
+        outputs.extend((n - topology.outputs..n).map(|j| self.rate(j, states[j])));
+        outputs
+    }
+
+    fn rate(&self, neuron: usize, state: f32) -> f32 {
+        self.topology
+            .activation
+            .apply(self.gains[neuron] * (state + self.biases[neuron]))
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn topology() -> CtrnnTopology {
+        CtrnnTopology {
+            inputs: 1,
+            neurons: 2,
+            outputs: 1,
+            activation: Activation::Identity,
+            step: 0.5,
+        }
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Ctrnn::random(&mut rng, &topology());
+        let weights: Vec<f32> = network.weights().collect();
+
+        assert_eq!(weights.len(), Ctrnn::parameter_count(&topology()));
+        assert_relative_eq!(
+            Ctrnn::from_weights(&topology(), weights.clone())
+                .weights()
+                .collect::<Vec<_>>()
+                .as_slice(),
+            weights.as_slice()
+        );
+    }
+
+    #[test]
+    fn integrates_towards_input() {
+        // neuron 0 ignores everything, neuron 1 is driven by the input
+        #[rustfmt::skip]
+        let network = Ctrnn::from_weights(&topology(), [
+            1.0, 0.0, 1.0, 0.0, 0.0, 0.0,
+            1.0, 0.0, 1.0, 2.0, 0.0, 0.0,
+        ]);
+        let mut state = network.state();
+        let mut scratch = Scratch::new();
+
+        let outputs: Vec<f32> = (0..3)
+            .map(|_| network.propagate_with_state(&[1.0], &mut state, &mut scratch)[0])
+            .collect();
+
+        // y += 0.5 * (2 - y)
+        assert_relative_eq!(outputs.as_slice(), [1.0, 1.5, 1.75].as_ref());
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_topologies() {
+        for topology in [
+            CtrnnTopology {
+                outputs: 3,
+                ..topology()
+            },
+            CtrnnTopology {
+                step: 0.0,
+                ..topology()
+            },
+            CtrnnTopology {
+                step: f32::NAN,
+                ..topology()
+            },
+        ] {
+            let weights = vec![0.0; Ctrnn::parameter_count(&topology)];
+
+            assert_eq!(
+                Ctrnn::try_from_weights(&topology, weights).unwrap_err(),
+                Error::InvalidCtrnnTopology
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_uncountable_weights() {
+        let topology = CtrnnTopology {
+            neurons: usize::MAX / 2,
+            ..topology()
+        };
+
+        assert_eq!(
+            Ctrnn::try_parameter_count(&topology),
+            Err(Error::InvalidCtrnnTopology)
+        );
+        assert_eq!(
+            Ctrnn::try_from_weights(&topology, []).unwrap_err(),
+            Error::InvalidCtrnnTopology
+        );
+    }
+}
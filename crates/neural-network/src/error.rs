@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Why a network could not be built or propagated, returned by the `try_`
+/// counterparts of the panicking constructors and methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A topology needs at least two layers, all of them with neurons
+    InvalidTopology,
+    /// The weights do not fit the topology, e.g. a genotype evolved for
+    /// another topology
+    WeightCountMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// The topology is made of more weights than can be counted, e.g. one
+    /// read from a corrupt file
+    TooManyParameters,
+    InputSizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// A CTRNN needs neurons, at most as many outputs as neurons, a
+    /// positive integration step and no more weights than can be counted
+    InvalidCtrnnTopology,
+    /// Training was given no samples
+    NoSamples,
+    /// Independent samples cannot train recurrent layers, which need
+    /// sequences
+    RecurrentNetwork,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidTopology => {
+                write!(f, "topology needs at least two layers with neurons")
+            }
+            Self::WeightCountMismatch { expected, actual } => {
+                write!(f, "expected {expected} weights, got {actual}")
+            }
+            Self::TooManyParameters => write!(f, "topology has too many weights"),
+            Self::InputSizeMismatch { expected, actual } => {
+                write!(f, "expected {expected} inputs, got {actual}")
+            }
+            Self::InvalidCtrnnTopology => write!(
+                f,
+                "CTRNN needs neurons, no more outputs than neurons, a positive step and countable weights"
+            ),
+            Self::NoSamples => write!(f, "got no samples to train on"),
+            Self::RecurrentNetwork => {
+                write!(f, "recurrent networks must be trained on sequences")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,162 @@
+use crate::{Activation, LayerKind, LayerTopology};
+
+/// Meaning of a group of consecutive network outputs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Head {
+    /// `size` values passed through `activation`, e.g. steering deltas
+    Continuous { size: usize, activation: Activation },
+    /// One of `choices` actions, picked with softmax over their outputs
+    Discrete { choices: usize },
+}
+
+impl Head {
+    /// Number of network outputs the head reads
+    pub fn size(&self) -> usize {
+        match *self {
+            Self::Continuous { size, .. } => size,
+            Self::Discrete { choices } => choices,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadOutput {
+    Continuous(Vec<f32>),
+    Discrete {
+        /// Softmax of the head's outputs, summing up to one
+        probabilities: Vec<f32>,
+        /// Most likely choice
+        choice: usize,
+    },
+}
+
+/// Splits the outputs of a network into heads with their own semantics.
+///
+/// The heads do their own activation, so the last layer of the network
+/// should pass its weighted sums through unchanged, see
+/// [`OutputHeads::layer`]. A network with a single discrete head learns its
+/// choices with [`Loss::SoftmaxCrossEntropy`](crate::Loss::SoftmaxCrossEntropy).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputHeads {
+    heads: Vec<Head>,
+}
+
+impl OutputHeads {
+    pub fn new(heads: Vec<Head>) -> Self {
+        assert!(!heads.is_empty());
+        assert!(heads.iter().all(|head| head.size() > 0));
+
+        Self { heads }
+    }
+
+    pub fn heads(&self) -> &[Head] {
+        &self.heads
+    }
+
+    /// Number of network outputs read by all heads together
+    pub fn size(&self) -> usize {
+        self.heads.iter().map(Head::size).sum()
+    }
+
+    /// Output layer producing the raw values read by the heads
+    pub fn layer(&self) -> LayerTopology {
+        LayerTopology {
+            neurons: self.size(),
+            activation: Activation::Identity,
+            kind: LayerKind::Dense,
+        }
+    }
+
+    pub fn apply(&self, outputs: &[f32]) -> Vec<HeadOutput> {
+        assert_eq!(outputs.len(), self.size());
+
+        let mut outputs = outputs;
+        self.heads
+            .iter()
+            .map(|head| {
+                let (values, rest) = outputs.split_at(head.size());
+                outputs = rest;
+
+                match *head {
+                    Head::Continuous { activation, .. } => HeadOutput::Continuous(
+                        values
+                            .iter()
+                            .map(|&value| activation.apply(value))
+                            .collect(),
+                    ),
+                    Head::Discrete { .. } => HeadOutput::Discrete {
+                        probabilities: softmax(values),
+                        choice: argmax(values),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Turns `logits` into probabilities summing up to one
+pub fn softmax(logits: &[f32]) -> Vec<f32> {
+    // shifting by the maximum keeps `exp` from overflowing
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits.iter().map(|logit| (logit - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+
+    exps.into_iter().map(|exp| exp / sum).collect()
+}
+
+/// Index of the largest value, the first one on ties
+pub fn argmax(values: &[f32]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (index, &value)| {
+            if value > best.1 { (index, value) } else { best }
+        })
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn softmax_is_stable() {
+        let probabilities = softmax(&[1000.0, 1000.0, 999.0]);
+
+        assert_relative_eq!(probabilities.iter().sum::<f32>(), 1.0);
+        assert_relative_eq!(probabilities[0], probabilities[1]);
+        assert!(probabilities[2] < probabilities[0]);
+    }
+
+    #[test]
+    fn apply_splits_outputs() {
+        let heads = OutputHeads::new(vec![
+            Head::Continuous {
+                size: 2,
+                activation: Activation::Tanh,
+            },
+            Head::Discrete { choices: 3 },
+        ]);
+
+        let outputs = heads.apply(&[0.0, 10.0, 0.5, 2.0, 0.5]);
+
+        assert_eq!(heads.layer().neurons, 5);
+        assert_eq!(outputs.len(), 2);
+        let HeadOutput::Continuous(steering) = &outputs[0] else {
+            panic!("expected a continuous head");
+        };
+        assert_relative_eq!(steering.as_slice(), [0.0, 10.0f32.tanh()].as_ref());
+
+        let HeadOutput::Discrete {
+            probabilities,
+            choice,
+        } = &outputs[1]
+        else {
+            panic!("expected a discrete head");
+        };
+        assert_eq!(*choice, 1);
+        assert_relative_eq!(probabilities.iter().sum::<f32>(), 1.0);
+    }
+}
@@ -0,0 +1,160 @@
+use rand::{Rng, RngCore};
+use rand_distr::StandardNormal;
+
+/// How the parameters of a freshly created [`Network`](crate::Network) are
+/// drawn, see [`Network::random_with`](crate::Network::random_with).
+///
+/// The default draws everything uniformly from `[-1, 1]`, which works for
+/// shallow networks; deeper stacks usually train better with
+/// [`Initializer::xavier`] for saturating activations and [`Initializer::he`]
+/// for ReLU.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Initializer {
+    pub weights: Init,
+    pub biases: Init,
+}
+
+/// Distribution of a single parameter. `fan_in` counts the inputs of a
+/// neuron, including its recurrent ones, and `fan_out` the neurons of its
+/// layer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Init {
+    /// Uniform from `[low, high]`
+    Uniform {
+        low: f32,
+        high: f32,
+    },
+    /// Normal with zero mean
+    Normal {
+        sigma: f32,
+    },
+    /// Glorot: uniform from `±sqrt(6 / (fan_in + fan_out))`
+    Xavier,
+    /// Kaiming: normal with `sigma = sqrt(2 / fan_in)`
+    He,
+    Zero,
+}
+
+impl Init {
+    pub fn sample(&self, rng: &mut dyn RngCore, fan_in: usize, fan_out: usize) -> f32 {
+        match *self {
+            Self::Uniform { low, high } => rng.random_range(low..=high),
+            Self::Normal { sigma } => sigma * rng.sample::<f32, _>(StandardNormal),
+            Self::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out) as f32).sqrt();
+                rng.random_range(-limit..=limit)
+            }
+            Self::He => {
+                let sigma = (2.0 / fan_in as f32).sqrt();
+                sigma * rng.sample::<f32, _>(StandardNormal)
+            }
+            Self::Zero => 0.0,
+        }
+    }
+}
+
+impl Initializer {
+    /// Weights and biases both uniform from `[low, high]`
+    pub fn uniform(low: f32, high: f32) -> Self {
+        assert!(low <= high);
+
+        Self {
+            weights: Init::Uniform { low, high },
+            biases: Init::Uniform { low, high },
+        }
+    }
+
+    /// Weights and biases both normal with zero mean
+    pub fn normal(sigma: f32) -> Self {
+        assert!(sigma >= 0.0);
+
+        Self {
+            weights: Init::Normal { sigma },
+            biases: Init::Normal { sigma },
+        }
+    }
+
+    /// Glorot-uniform weights, zero biases
+    pub fn xavier() -> Self {
+        Self {
+            weights: Init::Xavier,
+            biases: Init::Zero,
+        }
+    }
+
+    /// Kaiming-normal weights, zero biases
+    pub fn he() -> Self {
+        Self {
+            weights: Init::He,
+            biases: Init::Zero,
+        }
+    }
+
+    pub fn with_zero_biases(self) -> Self {
+        Self {
+            biases: Init::Zero,
+            ..self
+        }
+    }
+}
+
+impl Default for Initializer {
+    fn default() -> Self {
+        Self::uniform(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn samples(init: Init, fan_in: usize, fan_out: usize) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        (0..1000)
+            .map(|_| init.sample(&mut rng, fan_in, fan_out))
+            .collect()
+    }
+
+    fn std_dev(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+        variance.sqrt()
+    }
+
+    #[test]
+    fn xavier_stays_within_limit() {
+        let limit = (6.0f32 / 30.0).sqrt();
+
+        assert!(
+            samples(Init::Xavier, 20, 10)
+                .iter()
+                .all(|w| w.abs() <= limit)
+        );
+    }
+
+    #[test]
+    fn he_scales_with_fan_in() {
+        approx::assert_relative_eq!(std_dev(&samples(Init::He, 50, 10)), 0.2, max_relative = 0.1);
+    }
+
+    #[test]
+    fn normal_uses_sigma() {
+        approx::assert_relative_eq!(
+            std_dev(&samples(Init::Normal { sigma: 3.0 }, 1, 1)),
+            3.0,
+            max_relative = 0.1
+        );
+    }
+
+    #[test]
+    fn zero_biases() {
+        assert!(samples(Init::Zero, 4, 4).iter().all(|&b| b == 0.0));
+        assert_eq!(
+            Initializer::uniform(-0.5, 0.5).with_zero_biases().biases,
+            Init::Zero
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{Layer, Network};
+
+impl Network {
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].input_size()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].output_size()
+    }
+
+    /// Number of parameters, i.e. the length of [`Network::weights`]
+    pub fn weight_count(&self) -> usize {
+        self.layers.iter().map(Layer::weight_count).sum()
+    }
+
+    /// Like [`Network::propagate`], but returns the inputs followed by the
+    /// outputs of every layer, so the last entry holds the network outputs.
+    pub fn propagate_traced(&self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut trace = Vec::with_capacity(self.layers.len() + 1);
+        trace.push(inputs);
+
+        for layer in &self.layers {
+            let outputs = layer.propagate(trace[trace.len() - 1].clone());
+            trace.push(outputs);
+        }
+
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Activation, LayerKind, LayerTopology};
+
+    use super::*;
+
+    fn network() -> Network {
+        let topology = [
+            (2, LayerKind::Dense),
+            (3, LayerKind::Elman),
+            (1, LayerKind::Gru),
+        ]
+        .map(|(neurons, kind)| LayerTopology {
+            neurons,
+            activation: Activation::Tanh,
+            kind,
+        });
+        let count = Network::parameter_count(&topology);
+
+        Network::from_weights(&topology, (0..count).map(|i| (i as f32 * 0.37).sin()))
+    }
+
+    #[test]
+    fn shapes() {
+        let network = network();
+
+        assert_eq!(network.layers().len(), 2);
+        assert_eq!((network.input_size(), network.output_size()), (2, 1));
+        assert_eq!(network.weight_count(), network.weights().count());
+
+        let hidden = &network.layers()[0];
+        assert_eq!(hidden.input_weights().len(), 3 * 2);
+        assert_eq!(hidden.recurrent_weights().len(), 3 * 3);
+        assert_eq!(hidden.biases().len(), 3);
+        assert_eq!(network.layers()[1].biases().len(), 3);
+    }
+
+    #[test]
+    fn propagate_traced_ends_with_outputs() {
+        let network = network();
+        let trace = network.propagate_traced(vec![0.5, -1.0]);
+
+        assert_eq!(
+            trace.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 3, 1]
+        );
+        assert_eq!(trace[0], vec![0.5, -1.0]);
+        assert_eq!(trace[2], network.propagate(vec![0.5, -1.0]));
+    }
+}
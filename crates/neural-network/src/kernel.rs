@@ -0,0 +1,115 @@
+//! Forward pass shared by [`Layer`](crate::Layer) and
+//! [`NetworkBatch`](crate::NetworkBatch).
+//!
+//! Every buffer is laid out with the network index innermost, e.g. weights
+//! are `[row][column][network]` and activations `[neuron][network]`. A single
+//! network is simply the case `n == 1`, where this is plain row-major.
+
+use crate::{Activation, LayerKind};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Params<'a> {
+    pub(crate) kind: LayerKind,
+    pub(crate) activation: Activation,
+    pub(crate) input_size: usize,
+    pub(crate) output_size: usize,
+    /// `[gate * output][input][network]`
+    pub(crate) weights: &'a [f32],
+    /// `[gate * output][output][network]`, empty for dense layers
+    pub(crate) recurrent: &'a [f32],
+    /// `[gate * output][network]`
+    pub(crate) biases: &'a [f32],
+}
+
+/// Computes the outputs of `n` networks at once.
+///
+/// `hidden` is the previous output of a recurrent layer; `None` behaves like
+/// an all-zero state. `gates` is only used as temporary storage.
+pub(crate) fn forward(
+    params: Params,
+    n: usize,
+    inputs: &[f32],
+    hidden: Option<&[f32]>,
+    outputs: &mut Vec<f32>,
+    gates: &mut Vec<f32>,
+) {
+    let Params {
+        kind,
+        activation,
+        input_size,
+        output_size,
+        weights,
+        recurrent,
+        biases,
+    } = params;
+    let width = output_size * n;
+
+    match kind {
+        LayerKind::Dense | LayerKind::Elman => {
+            outputs.clear();
+            outputs.extend_from_slice(biases);
+            affine(input_size, n, weights, inputs, outputs);
+            if let Some(hidden) = hidden {
+                affine(output_size, n, recurrent, hidden, outputs);
+            }
+            for output in outputs.iter_mut() {
+                *output = activation.apply(*output);
+            }
+        }
+
+        LayerKind::Gru => {
+            gates.clear();
+            gates.extend_from_slice(biases);
+            affine(input_size, n, weights, inputs, gates);
+
+            let (update_and_reset, candidate) = gates.split_at_mut(2 * width);
+            let block = output_size * output_size * n;
+
+            outputs.clear();
+            if let Some(hidden) = hidden {
+                affine(
+                    output_size,
+                    n,
+                    &recurrent[..2 * block],
+                    hidden,
+                    update_and_reset,
+                );
+            }
+            for gate in update_and_reset.iter_mut() {
+                *gate = Activation::Sigmoid.apply(*gate);
+            }
+
+            let (update, reset) = update_and_reset.split_at(width);
+            if let Some(hidden) = hidden {
+                // reuse the outputs as storage for `reset * hidden`
+                outputs.extend(reset.iter().zip(hidden).map(|(r, h)| r * h));
+                affine(output_size, n, &recurrent[2 * block..], outputs, candidate);
+            }
+            for value in candidate.iter_mut() {
+                *value = activation.apply(*value);
+            }
+
+            outputs.clear();
+            outputs.extend(update.iter().zip(candidate.iter()).enumerate().map(
+                |(index, (z, candidate))| {
+                    let previous = hidden.map_or(0.0, |hidden| hidden[index]);
+                    (1.0 - z) * previous + z * candidate
+                },
+            ));
+        }
+    }
+}
+
+/// `outputs[row][k] += Σ matrix[row][column][k] * inputs[column][k]`
+fn affine(columns: usize, n: usize, matrix: &[f32], inputs: &[f32], outputs: &mut [f32]) {
+    for (outputs, matrix) in outputs
+        .chunks_exact_mut(n)
+        .zip(matrix.chunks_exact(columns * n))
+    {
+        for (weights, inputs) in matrix.chunks_exact(n).zip(inputs.chunks_exact(n)) {
+            for ((output, weight), input) in outputs.iter_mut().zip(weights).zip(inputs) {
+                *output += weight * input;
+            }
+        }
+    }
+}
@@ -1,83 +1,376 @@
+use std::iter::once;
+
 use rand::RngCore;
 
-use crate::neuron::Neuron;
+use crate::{
+    activation::Activation,
+    initializer::Initializer,
+    kernel::{self, Params},
+    layer_topology::{LayerKind, LayerTopology},
+};
 
+/// Fully connected layer backed by a single row-major weight matrix: row `i`
+/// holds the `input_size` incoming weights of output neuron `i`.
+///
+/// Recurrent layers additionally keep a row-major matrix of weights applied
+/// to their own previous outputs. A GRU has three rows per neuron, for its
+/// update gate, reset gate and candidate output, stored block after block.
 #[derive(Debug, Clone)]
 pub struct Layer {
-    pub(crate) neurons: Vec<Neuron>,
+    pub(crate) input_size: usize,
+    pub(crate) output_size: usize,
+    pub(crate) kind: LayerKind,
+    pub(crate) weights: Vec<f32>,
+    pub(crate) recurrent: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+    pub(crate) activation: Activation,
 }
 
 impl Layer {
     #[allow(dead_code)]
-    pub(crate) fn new(neurons: Vec<Neuron>) -> Self {
-        assert!(!neurons.is_empty());
-
-        assert!(
-            neurons
-                .iter()
-                .all(|neuron| neuron.weights.len() == neurons[0].weights.len())
-        );
+    pub(crate) fn new(
+        input_size: usize,
+        biases: Vec<f32>,
+        weights: Vec<f32>,
+        activation: Activation,
+    ) -> Self {
+        assert!(input_size > 0);
+        assert!(!biases.is_empty());
+        assert_eq!(weights.len(), input_size * biases.len());
 
-        Self { neurons }
+        Self {
+            input_size,
+            output_size: biases.len(),
+            kind: LayerKind::Dense,
+            weights,
+            recurrent: Vec::new(),
+            biases,
+            activation,
+        }
     }
-    pub(crate) fn random(rng: &mut dyn RngCore, input_size: usize, output_size: usize) -> Self {
-        let neurons = (0..output_size)
-            .map(|_| Neuron::random(rng, input_size))
-            .collect();
-        Self { neurons }
+
+    pub(crate) fn random(
+        rng: &mut dyn RngCore,
+        input_size: usize,
+        layer: &LayerTopology,
+        init: &Initializer,
+    ) -> Self {
+        let recurrent_size = if layer.kind.is_recurrent() {
+            layer.neurons
+        } else {
+            0
+        };
+        let (fan_in, fan_out) = (input_size + recurrent_size, layer.neurons);
+
+        let mut params = Vec::new();
+        for _ in 0..layer.kind.gates() * layer.neurons {
+            params.push(init.biases.sample(rng, fan_in, fan_out));
+            params.extend((0..fan_in).map(|_| init.weights.sample(rng, fan_in, fan_out)));
+        }
+
+        Self::from_weights(input_size, layer, &mut params.into_iter())
     }
 
+    /// Expects the weights in the same order as [`Layer::weights`]: for every
+    /// row its bias, its `input_size` incoming weights and, for recurrent
+    /// layers, its `output_size` recurrent weights.
     pub fn from_weights(
         input_size: usize,
-        output_size: usize,
+        layer: &LayerTopology,
         weights: &mut dyn Iterator<Item = f32>,
     ) -> Self {
-        let neurons = (0..output_size)
-            .map(|_| Neuron::from_weights(input_size, weights))
-            .collect();
+        let output_size = layer.neurons;
+        let rows = layer.kind.gates() * output_size;
+        let recurrent_size = if layer.kind.is_recurrent() {
+            output_size
+        } else {
+            0
+        };
+
+        let mut biases = Vec::with_capacity(rows);
+        let mut matrix = Vec::with_capacity(rows * input_size);
+        let mut recurrent = Vec::with_capacity(rows * recurrent_size);
+        let mut next = || weights.next().expect("got not enough weights");
+
+        for _ in 0..rows {
+            biases.push(next());
+            matrix.extend((0..input_size).map(|_| next()));
+            recurrent.extend((0..recurrent_size).map(|_| next()));
+        }
+
+        Self {
+            input_size,
+            output_size,
+            kind: layer.kind,
+            weights: matrix,
+            recurrent,
+            biases,
+            activation: layer.activation,
+        }
+    }
+
+    /// Number of parameters a layer of this shape is built from, `None`
+    /// when it does not fit in a `usize`
+    pub(crate) fn parameter_count(input_size: usize, layer: &LayerTopology) -> Option<usize> {
+        let recurrent_size = if layer.kind.is_recurrent() {
+            layer.neurons
+        } else {
+            0
+        };
+        let row_size = input_size.checked_add(recurrent_size)?.checked_add(1)?;
+        layer
+            .kind
+            .gates()
+            .checked_mul(layer.neurons)?
+            .checked_mul(row_size)
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub fn kind(&self) -> LayerKind {
+        self.kind
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
 
-        Self { neurons }
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    /// Row-major `[row][input]` weights. A GRU has three blocks of
+    /// `output_size` rows: its update gate, reset gate and candidate output.
+    pub fn input_weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Row-major `[row][output]` weights applied to the previous outputs,
+    /// empty for dense layers
+    pub fn recurrent_weights(&self) -> &[f32] {
+        &self.recurrent
+    }
+
+    /// One bias per row
+    pub fn biases(&self) -> &[f32] {
+        &self.biases
+    }
+
+    pub fn weight_count(&self) -> usize {
+        self.biases.len() + self.weights.len() + self.recurrent.len()
+    }
+
+    /// Flattened parameters, bias first for every row, matching the
+    /// ordering genotypes have always been built from.
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        let recurrent_size = self.recurrent.len() / self.biases.len();
+
+        (0..self.biases.len()).flat_map(move |row| {
+            once(&self.biases[row])
+                .chain(&self.weights[row * self.input_size..][..self.input_size])
+                .chain(&self.recurrent[row * recurrent_size..][..recurrent_size])
+                .copied()
+        })
+    }
+
+    pub(crate) fn params(&self) -> Params<'_> {
+        Params {
+            kind: self.kind,
+            activation: self.activation,
+            input_size: self.input_size,
+            output_size: self.output_size,
+            weights: &self.weights,
+            recurrent: &self.recurrent,
+            biases: &self.biases,
+        }
     }
 
     pub(crate) fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.neurons
-            .iter()
-            .map(|neuron| neuron.propagate(&inputs))
-            .collect()
+        let mut outputs = Vec::with_capacity(self.output_size);
+        self.propagate_into(&inputs, &mut outputs);
+        outputs
+    }
+
+    /// Writes the layer outputs into `outputs`, reusing its allocation.
+    /// Recurrent layers behave as if their previous outputs were all zero.
+    pub fn propagate_into(&self, inputs: &[f32], outputs: &mut Vec<f32>) {
+        self.forward(inputs, None, outputs, &mut Vec::new());
+    }
+
+    pub(crate) fn forward(
+        &self,
+        inputs: &[f32],
+        hidden: Option<&[f32]>,
+        outputs: &mut Vec<f32>,
+        gates: &mut Vec<f32>,
+    ) {
+        assert_eq!(inputs.len(), self.input_size);
+
+        kernel::forward(self.params(), 1, inputs, hidden, outputs, gates);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use approx::assert_relative_eq;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
     use super::*;
 
+    fn topology(neurons: usize, activation: Activation, kind: LayerKind) -> LayerTopology {
+        LayerTopology {
+            neurons,
+            activation,
+            kind,
+        }
+    }
+
     #[test]
     fn random() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
-        let layer = Layer::random(&mut rng, 4, 3);
+        let layer = Layer::random(
+            &mut rng,
+            4,
+            &topology(3, Activation::Relu, LayerKind::Dense),
+            &Initializer::default(),
+        );
         let expected_biases: Vec<f32> = vec![-0.6255188, -0.5351684, -0.19277143];
 
-        approx::assert_relative_eq!(
-            layer
-                .neurons
-                .iter()
-                .map(|n| n.bias)
-                .collect::<Vec<f32>>()
-                .as_slice(),
-            expected_biases.as_ref()
+        assert_relative_eq!(layer.biases.as_slice(), expected_biases.as_ref());
+        assert_relative_eq!(
+            &layer.weights[..4],
+            &[0.67383933, 0.81812596, 0.26284885, 0.5238805].as_ref()
         );
     }
 
     #[test]
     fn propagate() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
-        let layer = Layer::random(&mut rng, 4, 3);
+        let layer = Layer::random(
+            &mut rng,
+            4,
+            &topology(3, Activation::Relu, LayerKind::Dense),
+            &Initializer::default(),
+        );
         let inputs = vec![0.2, 0.5, 1.0, 0.8];
         let expected = &[0.60026526, 0.0, 0.0];
 
-        approx::assert_relative_eq!(layer.propagate(inputs).as_slice(), expected.as_ref());
+        assert_relative_eq!(layer.propagate(inputs).as_slice(), expected.as_ref());
+    }
+
+    #[test]
+    fn propagate_single_neuron() {
+        let layer = Layer::new(2, vec![0.5], vec![-0.3, 0.8], Activation::Relu);
+
+        assert_relative_eq!(layer.propagate(vec![-10.0, -10.0])[0], 0.0);
+        assert_relative_eq!(
+            layer.propagate(vec![0.5, 1.0])[0],
+            (-0.3 * 0.5) + (0.8 * 1.0) + 0.5
+        );
+    }
+
+    #[test]
+    fn propagate_with_activation() {
+        let layer = Layer::new(
+            2,
+            vec![0.0, 0.1],
+            vec![-0.5, 0.5, 0.2, 0.3],
+            Activation::Tanh,
+        );
+        let expected = &[(-0.25f32).tanh(), 0.45f32.tanh()];
+
+        assert_relative_eq!(
+            layer.propagate(vec![1.0, 0.5]).as_slice(),
+            expected.as_ref()
+        );
+    }
+
+    #[test]
+    fn propagate_into_reuses_outputs() {
+        let layer = Layer::new(
+            2,
+            vec![0.0, 0.1],
+            vec![-0.5, 0.5, 0.2, 0.3],
+            Activation::Identity,
+        );
+        let mut outputs = vec![9.0; 5];
+
+        layer.propagate_into(&[1.0, 0.5], &mut outputs);
+
+        assert_relative_eq!(outputs.as_slice(), [-0.25, 0.45].as_ref());
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let layer = Layer::new(
+            2,
+            vec![0.1, 0.2],
+            vec![1.0, 2.0, 3.0, 4.0],
+            Activation::Relu,
+        );
+        let weights: Vec<f32> = layer.weights().collect();
+
+        assert_relative_eq!(weights.as_slice(), [0.1, 1.0, 2.0, 0.2, 3.0, 4.0].as_ref());
+
+        let rebuilt = Layer::from_weights(
+            2,
+            &topology(2, Activation::Relu, LayerKind::Dense),
+            &mut weights.into_iter(),
+        );
+        assert_relative_eq!(rebuilt.biases.as_slice(), layer.biases.as_slice());
+        assert_relative_eq!(rebuilt.weights.as_slice(), layer.weights.as_slice());
+    }
+
+    #[test]
+    fn recurrent_weights_round_trip() {
+        for kind in [LayerKind::Elman, LayerKind::Gru] {
+            let layer = topology(2, Activation::Tanh, kind);
+            let count = Layer::parameter_count(3, &layer).unwrap();
+            let weights: Vec<f32> = (0..count).map(|i| i as f32).collect();
+
+            let rebuilt = Layer::from_weights(3, &layer, &mut weights.iter().copied());
+
+            assert_eq!(rebuilt.biases.len(), kind.gates() * 2);
+            assert_eq!(rebuilt.recurrent.len(), kind.gates() * 2 * 2);
+            assert_relative_eq!(
+                rebuilt.weights().collect::<Vec<_>>().as_slice(),
+                weights.as_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn elman_feeds_back_previous_outputs() {
+        // bias, input weight, recurrent weight
+        let layer = Layer::from_weights(
+            1,
+            &topology(1, Activation::Identity, LayerKind::Elman),
+            &mut [0.0, 1.0, 0.5].into_iter(),
+        );
+        let mut outputs = Vec::new();
+
+        layer.forward(&[2.0], Some(&[4.0]), &mut outputs, &mut Vec::new());
+
+        assert_relative_eq!(outputs[0], 2.0 + 0.5 * 4.0);
+    }
+
+    #[test]
+    fn gru_interpolates_between_previous_and_candidate() {
+        // update gate, reset gate and candidate rows: bias, input, recurrent
+        let layer = Layer::from_weights(
+            1,
+            &topology(1, Activation::Identity, LayerKind::Gru),
+            &mut [0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 1.0, 1.0].into_iter(),
+        );
+        let mut outputs = Vec::new();
+
+        layer.forward(&[3.0], Some(&[2.0]), &mut outputs, &mut Vec::new());
+
+        // z = sigmoid(0) = 0.5, r = sigmoid(10), candidate = 3 + r * 2
+        let r = Activation::Sigmoid.apply(10.0);
+        assert_relative_eq!(outputs[0], 0.5 * 2.0 + 0.5 * (3.0 + r * 2.0));
     }
 }
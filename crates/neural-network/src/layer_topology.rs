@@ -1,4 +1,40 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+use crate::activation::Activation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct LayerTopology {
     pub neurons: usize,
+    /// Applied to the outputs of this layer, ignored for the input layer
+    pub activation: Activation,
+    /// Ignored for the input layer
+    #[serde(default)]
+    pub kind: LayerKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayerKind {
+    /// Plain fully connected layer without memory
+    #[default]
+    Dense,
+    /// Fully connected layer that also sees its own previous outputs
+    /// (Elman-style context units)
+    Elman,
+    /// Gated recurrent unit: update and reset gates decide how much of the
+    /// previous output is kept; `activation` applies to the candidate output
+    Gru,
+}
+
+impl LayerKind {
+    pub fn is_recurrent(self) -> bool {
+        self != Self::Dense
+    }
+
+    /// How many sets of weights every neuron has
+    pub(crate) fn gates(self) -> usize {
+        match self {
+            Self::Dense | Self::Elman => 1,
+            Self::Gru => 3,
+        }
+    }
 }
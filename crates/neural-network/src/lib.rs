@@ -1,13 +1,54 @@
+mod activation;
+mod batch;
+mod controller;
+mod ctrnn;
+mod error;
+mod heads;
+mod initializer;
+mod introspection;
+mod kernel;
 mod layer;
 mod layer_topology;
-mod neuron;
+mod neat;
+mod persistence;
+mod prune;
+mod quantized;
+mod render;
+mod scratch;
+mod state;
+mod training;
 
 use std::iter::once;
 
-pub use self::{layer::Layer, layer_topology::*};
+pub use self::{
+    activation::Activation,
+    batch::NetworkBatch,
+    controller::{Controller, CtrnnController, NetworkController},
+    ctrnn::{Ctrnn, CtrnnTopology},
+    error::Error,
+    heads::{Head, HeadOutput, OutputHeads, argmax, softmax},
+    initializer::{Init, Initializer},
+    layer::Layer,
+    layer_topology::*,
+    neat::{
+        ConnectionGene, Innovations, NeatController, NeatGenome, NeatMutation, NeatNetwork,
+        NodeGene, NodeKind,
+    },
+    persistence::{FORMAT_VERSION, Format, PersistenceError},
+    prune::{PruneOptions, PruneReport},
+    quantized::QuantizedNetwork,
+    render::RenderOptions,
+    scratch::Scratch,
+    state::State,
+    training::{Loss, Optimizer, Trainer},
+};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::persistence::SerializedNetwork;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "SerializedNetwork", try_from = "SerializedNetwork")]
 pub struct Network {
     pub(crate) layers: Vec<Layer>,
 }
@@ -24,23 +65,46 @@ impl Network {
     ///
     /// Layer::random(
     ///   vec![
-    ///     LayerTopology { neurons: 3},
-    ///     LayerTopology { neurons: 2},
-    ///     LayerTopology { neurons: 1}
+    ///     LayerTopology { neurons: 3, activation: Activation::Relu, kind: LayerKind::Dense },
+    ///     LayerTopology { neurons: 2, activation: Activation::Relu, kind: LayerKind::Elman },
+    ///     LayerTopology { neurons: 1, activation: Activation::Tanh, kind: LayerKind::Dense }
     ///   ]
     /// );
     /// ```
     /// means that the there are two layers:
-    /// - the first with 3 inputs and 2 outputs
-    /// - the second with 2 inputs and 1 output!
+    /// - the first with 3 inputs and 2 outputs, activated by ReLU, which also
+    ///   sees its own previous outputs
+    /// - the second with 2 inputs and 1 output, activated by tanh!
     pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
-        assert!(layers.len() > 1);
+        Self::random_with(rng, layers, &Initializer::default())
+    }
+
+    /// Like [`Network::random`], but with the parameters drawn by `init`,
+    /// e.g. [`Initializer::he`] for deep ReLU stacks.
+    pub fn random_with(
+        rng: &mut dyn RngCore,
+        layers: &[LayerTopology],
+        init: &Initializer,
+    ) -> Self {
+        Self::try_random_with(rng, layers, init).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Result<Self, Error> {
+        Self::try_random_with(rng, layers, &Initializer::default())
+    }
+
+    pub fn try_random_with(
+        rng: &mut dyn RngCore,
+        layers: &[LayerTopology],
+        init: &Initializer,
+    ) -> Result<Self, Error> {
+        check_topology(layers)?;
 
         let layers = layers
             .windows(2)
-            .map(|layers| Layer::random(rng, layers[0].neurons, layers[1].neurons))
+            .map(|layers| Layer::random(rng, layers[0].neurons, &layers[1], init))
             .collect();
-        Self { layers }
+        Ok(Self { layers })
     }
 
     pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
@@ -49,29 +113,117 @@ impl Network {
             .fold(inputs, |inputs, layer| layer.propagate(inputs))
     }
 
+    pub fn try_propagate(&self, inputs: Vec<f32>) -> Result<Vec<f32>, Error> {
+        let expected = self.layers[0].input_size();
+        if inputs.len() != expected {
+            return Err(Error::InputSizeMismatch {
+                expected,
+                actual: inputs.len(),
+            });
+        }
+
+        Ok(self.propagate(inputs))
+    }
+
+    /// Reconstructs the topology the network was built from. The input
+    /// layer carries no activation of its own and is reported as dense identity.
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let input = LayerTopology {
+            neurons: self.layers[0].input_size(),
+            activation: Activation::Identity,
+            kind: LayerKind::Dense,
+        };
+
+        once(input)
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.output_size(),
+                activation: layer.activation,
+                kind: layer.kind,
+            }))
+            .collect()
+    }
+
+    /// Fresh memory for the recurrent layers, to be used with
+    /// [`Network::propagate_with_state`]
+    pub fn state(&self) -> State {
+        State::new(self.layers.iter().map(|layer| {
+            if layer.kind.is_recurrent() {
+                layer.output_size
+            } else {
+                0
+            }
+        }))
+    }
+
     pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
-        self.layers
-            .iter()
-            .flat_map(|layer| layer.neurons.iter())
-            .flat_map(|neuron| once(&neuron.bias).chain(&neuron.weights))
-            .copied()
+        self.layers.iter().flat_map(|layer| layer.weights())
     }
 
     pub fn from_weights(layers: &[LayerTopology], weights: impl IntoIterator<Item = f32>) -> Self {
-        assert!(layers.len() > 1);
+        Self::try_from_weights(layers, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let mut weights = weights.into_iter();
+    pub fn try_from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, Error> {
+        check_topology(layers)?;
 
+        let weights: Vec<f32> = weights.into_iter().collect();
+        let expected = Self::try_parameter_count(layers)?;
+        if weights.len() != expected {
+            return Err(Error::WeightCountMismatch {
+                expected,
+                actual: weights.len(),
+            });
+        }
+
+        let mut weights = weights.into_iter();
         let layers = layers
             .windows(2)
-            .map(|layers| Layer::from_weights(layers[0].neurons, layers[1].neurons, &mut weights))
+            .map(|layers| Layer::from_weights(layers[0].neurons, &layers[1], &mut weights))
             .collect();
 
-        if weights.next().is_some() {
-            panic!("got too many weights");
-        }
+        Ok(Self { layers })
+    }
 
-        Self { layers }
+    /// Number of weights a network of this topology is built from, i.e. the
+    /// length of genotypes describing it
+    pub fn parameter_count(layers: &[LayerTopology]) -> usize {
+        Self::try_parameter_count(layers).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with [`Error::TooManyParameters`] instead of overflowing
+    pub fn try_parameter_count(layers: &[LayerTopology]) -> Result<usize, Error> {
+        layers.windows(2).try_fold(0usize, |count, layers| {
+            Layer::parameter_count(layers[0].neurons, &layers[1])
+                .and_then(|layer| count.checked_add(layer))
+                .ok_or(Error::TooManyParameters)
+        })
+    }
+
+    /// Number of genes of every row of weights, in the order of
+    /// [`Network::weights`]: one row per neuron, or per gate and neuron for
+    /// GRU layers, made of its bias, input and recurrent weights.
+    ///
+    /// Meant as the groups of a crossover that keeps neurons whole.
+    pub fn row_sizes(layers: &[LayerTopology]) -> Vec<usize> {
+        layers
+            .windows(2)
+            .flat_map(|layers| {
+                let layer = &layers[1];
+                let rows = layer.kind.gates() * layer.neurons;
+                vec![Network::parameter_count(layers) / rows; rows]
+            })
+            .collect()
+    }
+}
+
+fn check_topology(layers: &[LayerTopology]) -> Result<(), Error> {
+    if layers.len() < 2 || layers.iter().any(|layer| layer.neurons == 0) {
+        Err(Error::InvalidTopology)
+    } else {
+        Ok(())
     }
 }
 
@@ -80,53 +232,116 @@ mod tests {
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
-    use crate::neuron::Neuron;
-
     use super::*;
 
     #[test]
     fn random() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
         let topology = vec![
-            LayerTopology { neurons: 3 },
-            LayerTopology { neurons: 2 },
-            LayerTopology { neurons: 1 },
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::Relu,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Relu,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+            },
         ];
         let network = Network::random(&mut rng, &topology);
 
         assert_eq!(network.layers.len(), 2);
+        assert_eq!(network.layers[0].activation, Activation::Relu);
+        assert_eq!(network.layers[1].activation, Activation::Tanh);
 
         let expected_biases_layer_1: Vec<f32> = vec![-0.6255188, 0.5238805];
         approx::assert_relative_eq!(
-            network.layers[0]
-                .neurons
-                .iter()
-                .map(|n| n.bias)
-                .collect::<Vec<f32>>()
-                .as_slice(),
+            network.layers[0].biases.as_slice(),
             expected_biases_layer_1.as_ref()
         );
 
         let expected_biases_layer_2: Vec<f32> = vec![-0.102499485];
         approx::assert_relative_eq!(
-            network.layers[1]
-                .neurons
-                .iter()
-                .map(|n| n.bias)
-                .collect::<Vec<f32>>()
-                .as_slice(),
+            network.layers[1].biases.as_slice(),
             expected_biases_layer_2.as_ref()
         );
     }
 
+    #[test]
+    fn random_with_is_reproducible() {
+        let topology = [4, 8, 2].map(|neurons| LayerTopology {
+            neurons,
+            activation: Activation::Relu,
+            kind: LayerKind::Dense,
+        });
+        let network = |init: &Initializer| {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            Network::random_with(&mut rng, &topology, init)
+                .weights()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(network(&Initializer::he()), network(&Initializer::he()));
+        assert_ne!(network(&Initializer::he()), network(&Initializer::xavier()));
+        assert!(
+            Network::random_with(
+                &mut ChaCha8Rng::from_seed(Default::default()),
+                &topology,
+                &Initializer::he()
+            )
+            .layers
+            .iter()
+            .all(|layer| layer.biases.iter().all(|&b| b == 0.0))
+        );
+    }
+
+    #[test]
+    fn try_from_weights_reports_mismatches() {
+        let topology = [2, 1].map(|neurons| LayerTopology {
+            neurons,
+            activation: Activation::Relu,
+            kind: LayerKind::Dense,
+        });
+
+        assert_eq!(
+            Network::try_from_weights(&topology, [1.0, 2.0]).unwrap_err(),
+            Error::WeightCountMismatch {
+                expected: 3,
+                actual: 2
+            }
+        );
+        assert_eq!(
+            Network::try_from_weights(&topology[..1], []).unwrap_err(),
+            Error::InvalidTopology
+        );
+
+        let network = Network::try_from_weights(&topology, [0.0, 1.0, 1.0]).unwrap();
+        assert_eq!(
+            network.try_propagate(vec![1.0]).unwrap_err(),
+            Error::InputSizeMismatch {
+                expected: 2,
+                actual: 1
+            }
+        );
+        assert_eq!(network.try_propagate(vec![1.0, 2.0]), Ok(vec![3.0]));
+    }
+
     #[test]
     fn propagate() {
         let layers = (
-            Layer::new(vec![
-                Neuron::new(0.0, vec![-0.5, -0.4, -0.3]),
-                Neuron::new(0.0, vec![-0.2, -0.1, 0.0]),
-            ]),
-            Layer::new(vec![Neuron::new(0.0, vec![-0.5, 0.5])]),
+            Layer::new(
+                3,
+                vec![0.0, 0.0],
+                vec![-0.5, -0.4, -0.3, -0.2, -0.1, 0.0],
+                Activation::Relu,
+            ),
+            Layer::new(2, vec![0.0], vec![-0.5, 0.5], Activation::Tanh),
         );
         let network = Network::new(vec![layers.0.clone(), layers.1.clone()]);
 
@@ -135,4 +350,82 @@ mod tests {
 
         approx::assert_relative_eq!(actual.as_slice(), expected.as_slice());
     }
+
+    #[test]
+    fn row_sizes_add_up_to_parameter_count() {
+        let topology = [
+            (3, LayerKind::Dense),
+            (4, LayerKind::Gru),
+            (2, LayerKind::Elman),
+        ]
+        .map(|(neurons, kind)| LayerTopology {
+            neurons,
+            activation: Activation::Tanh,
+            kind,
+        });
+
+        let sizes = Network::row_sizes(&topology);
+
+        assert_eq!(sizes, [vec![1 + 3 + 4; 12], vec![1 + 4 + 2; 2]].concat());
+        assert_eq!(
+            sizes.iter().sum::<usize>(),
+            Network::parameter_count(&topology)
+        );
+    }
+
+    #[test]
+    fn from_weights_preserves_activations() {
+        let topology = [
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Relu,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+        ];
+        let network = Network::from_weights(&topology, [0.5, -2.0, 1.0]);
+
+        assert_eq!(network.layers[0].activation, Activation::Identity);
+        approx::assert_relative_eq!(
+            network.propagate(vec![1.0, 0.5]).as_slice(),
+            [-1.0].as_ref()
+        );
+    }
+
+    #[test]
+    fn propagate_with_state_remembers_previous_inputs() {
+        let topology = [
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Elman,
+            },
+        ];
+        // bias, input weight, recurrent weight
+        let network = Network::from_weights(&topology, [0.0, 1.0, 0.5]);
+        let mut state = network.state();
+        let mut scratch = Scratch::new();
+
+        let outputs: Vec<f32> = [1.0, 0.0, 0.0]
+            .iter()
+            .map(|&input| network.propagate_with_state(&[input], &mut state, &mut scratch)[0])
+            .collect();
+        approx::assert_relative_eq!(outputs.as_slice(), [1.0, 0.5, 0.25].as_ref());
+
+        state.reset();
+        approx::assert_relative_eq!(
+            network.propagate_with_state(&[0.0], &mut state, &mut scratch)[0],
+            0.0
+        );
+        approx::assert_relative_eq!(network.propagate_into(&[1.0], &mut scratch)[0], 1.0);
+    }
 }
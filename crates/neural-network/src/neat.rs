@@ -0,0 +1,798 @@
+//! NEAT-style networks whose topology evolves along with their weights.
+//!
+//! A [`NeatGenome`] lists node genes and connection genes; every connection
+//! carries an innovation number handed out by [`Innovations`], so that
+//! genomes with different histories can still be lined up gene by gene
+//! during [`NeatGenome::crossover`].
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::{Rng, RngCore, seq::IndexedRandom};
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use crate::{Activation, Controller, Error};
+
+/// How many random node pairs [`NeatGenome::add_connection`] tries before
+/// giving up on a genome that is (nearly) fully connected
+const CONNECTION_ATTEMPTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    Input,
+    Output,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+    /// Unused by input nodes, which pass their input through unchanged
+    pub bias: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    /// Disabled connections are kept so that they can be inherited and
+    /// re-enabled by crossover
+    pub enabled: bool,
+}
+
+/// Node and connection genes of a feed-forward network.
+///
+/// Nodes are sorted by id, inputs coming first and outputs right after, and
+/// connections are sorted by innovation number. Connections never form a
+/// cycle, whether enabled or not.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeatGenome {
+    pub(crate) inputs: usize,
+    pub(crate) outputs: usize,
+    /// Applied by every hidden and output node
+    pub(crate) activation: Activation,
+    pub(crate) nodes: Vec<NodeGene>,
+    pub(crate) connections: Vec<ConnectionGene>,
+}
+
+impl NeatGenome {
+    /// Every input connected straight to every output, with weights and
+    /// biases drawn from `[-1, 1]`. Connection `input * outputs + output`
+    /// gets that innovation number in every minimal genome.
+    pub fn minimal(
+        rng: &mut dyn RngCore,
+        inputs: usize,
+        outputs: usize,
+        activation: Activation,
+    ) -> Self {
+        assert!(inputs > 0);
+        assert!(outputs > 0);
+
+        let nodes = (0..inputs + outputs)
+            .map(|id| {
+                if id < inputs {
+                    NodeGene {
+                        id,
+                        kind: NodeKind::Input,
+                        bias: 0.0,
+                    }
+                } else {
+                    NodeGene {
+                        id,
+                        kind: NodeKind::Output,
+                        bias: rng.random_range(-1.0..=1.0),
+                    }
+                }
+            })
+            .collect();
+
+        let connections = (0..inputs)
+            .flat_map(|input| (0..outputs).map(move |output| (input, output)))
+            .map(|(input, output)| ConnectionGene {
+                innovation: input * outputs + output,
+                from: input,
+                to: inputs + output,
+                weight: rng.random_range(-1.0..=1.0),
+                enabled: true,
+            })
+            .collect();
+
+        Self {
+            inputs,
+            outputs,
+            activation,
+            nodes,
+            connections,
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    /// Connection weights in innovation order, followed by the biases of
+    /// the hidden and output nodes in id order
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.connections
+            .iter()
+            .map(|connection| connection.weight)
+            .chain(self.biased_nodes().map(|node| node.bias))
+    }
+
+    /// Same topology with the values from [`NeatGenome::weights`] replaced
+    pub fn with_weights(&self, weights: &[f32]) -> Result<Self, Error> {
+        let expected = self.connections.len() + self.biased_nodes().count();
+        if weights.len() != expected {
+            return Err(Error::WeightCountMismatch {
+                expected,
+                actual: weights.len(),
+            });
+        }
+
+        let mut genome = self.clone();
+        let (connections, biases) = weights.split_at(genome.connections.len());
+        for (connection, &weight) in genome.connections.iter_mut().zip(connections) {
+            connection.weight = weight;
+        }
+        for (node, &bias) in genome
+            .nodes
+            .iter_mut()
+            .filter(|node| node.kind != NodeKind::Input)
+            .zip(biases)
+        {
+            node.bias = bias;
+        }
+
+        Ok(genome)
+    }
+
+    fn biased_nodes(&self) -> impl Iterator<Item = &NodeGene> {
+        self.nodes
+            .iter()
+            .filter(|node| node.kind != NodeKind::Input)
+    }
+
+    fn contains_node(&self, id: usize) -> bool {
+        self.nodes.binary_search_by_key(&id, |node| node.id).is_ok()
+    }
+
+    /// Whether `to` can be reached from `from` over any connection
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut pending = vec![from];
+        let mut seen = vec![from];
+
+        while let Some(node) = pending.pop() {
+            if node == to {
+                return true;
+            }
+            for connection in self.connections.iter().filter(|c| c.from == node) {
+                if !seen.contains(&connection.to) {
+                    seen.push(connection.to);
+                    pending.push(connection.to);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Connects two so far unconnected nodes with a random weight, keeping
+    /// the network feed-forward. Returns whether a connection was added.
+    pub fn add_connection(&mut self, rng: &mut dyn RngCore, innovations: &mut Innovations) -> bool {
+        for _ in 0..CONNECTION_ATTEMPTS {
+            let from = self.nodes.choose(rng).unwrap();
+            let to = self.nodes.choose(rng).unwrap();
+
+            if to.kind == NodeKind::Input
+                || from.kind == NodeKind::Output
+                || from.id == to.id
+                || self
+                    .connections
+                    .iter()
+                    .any(|c| c.from == from.id && c.to == to.id)
+                || self.reaches(to.id, from.id)
+            {
+                continue;
+            }
+
+            let (from, to) = (from.id, to.id);
+            self.insert_connection(ConnectionGene {
+                innovation: innovations.connection(from, to),
+                from,
+                to,
+                weight: rng.random_range(-1.0..=1.0),
+                enabled: true,
+            });
+            return true;
+        }
+
+        false
+    }
+
+    /// Splits a random enabled connection in two with a new hidden node in
+    /// between: the incoming half has weight 1, the outgoing half keeps the
+    /// old weight. Returns whether a node was added.
+    pub fn add_node(&mut self, rng: &mut dyn RngCore, innovations: &mut Innovations) -> bool {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        let Some(&index) = enabled.choose(rng) else {
+            return false;
+        };
+
+        let split = self.connections[index];
+        let node = innovations.split(split.innovation);
+        // this genome inherited the node through crossover already
+        if self.contains_node(node) {
+            return false;
+        }
+
+        self.connections[index].enabled = false;
+        let position = self.nodes.partition_point(|gene| gene.id < node);
+        self.nodes.insert(
+            position,
+            NodeGene {
+                id: node,
+                kind: NodeKind::Hidden,
+                bias: 0.0,
+            },
+        );
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(split.from, node),
+            from: split.from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(node, split.to),
+            from: node,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+
+        true
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let position = self
+            .connections
+            .partition_point(|gene| gene.innovation < connection.innovation);
+        self.connections.insert(position, connection);
+    }
+
+    /// Lines up the genes of both parents by innovation number: matching
+    /// genes are inherited from either parent at random, disjoint and excess
+    /// ones only from `fitter`. A gene disabled in either parent stays
+    /// disabled with a 75% chance.
+    pub fn crossover(rng: &mut dyn RngCore, fitter: &Self, other: &Self) -> Self {
+        assert_eq!(
+            (fitter.inputs, fitter.outputs),
+            (other.inputs, other.outputs),
+            "got genomes with different inputs or outputs"
+        );
+
+        let matching: HashMap<usize, &ConnectionGene> = other
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation, connection))
+            .collect();
+
+        let connections = fitter
+            .connections
+            .iter()
+            .map(|&gene| match matching.get(&gene.innovation) {
+                Some(&&other) => {
+                    let mut child = if rng.random_bool(0.5) { gene } else { other };
+                    child.enabled = if gene.enabled && other.enabled {
+                        true
+                    } else {
+                        rng.random_bool(0.25)
+                    };
+                    child
+                }
+                None => gene,
+            })
+            .collect();
+
+        let nodes = fitter
+            .nodes
+            .iter()
+            .map(
+                |&node| match other.nodes.binary_search_by_key(&node.id, |other| other.id) {
+                    Ok(index) if rng.random_bool(0.5) => other.nodes[index],
+                    _ => node,
+                },
+            )
+            .collect();
+
+        Self {
+            inputs: fitter.inputs,
+            outputs: fitter.outputs,
+            activation: fitter.activation,
+            nodes,
+            connections,
+        }
+    }
+
+    /// NEAT compatibility distance: disjoint and excess genes relative to
+    /// the size of the larger genome plus 0.4 times the average weight
+    /// difference of matching genes. Genomes closer than about 3 are
+    /// usually considered the same species.
+    pub fn compatibility(&self, other: &Self) -> f32 {
+        let others: HashMap<usize, f32> = other
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation, connection.weight))
+            .collect();
+
+        let (mut matching, mut difference) = (0, 0.0);
+        for connection in &self.connections {
+            if let Some(weight) = others.get(&connection.innovation) {
+                matching += 1;
+                difference += (connection.weight - weight).abs();
+            }
+        }
+
+        let unmatched = self.connections.len() + other.connections.len() - 2 * matching;
+        let size = self.connections.len().max(other.connections.len()).max(1);
+        let weights = if matching > 0 {
+            difference / matching as f32
+        } else {
+            0.0
+        };
+
+        unmatched as f32 / size as f32 + 0.4 * weights
+    }
+}
+
+/// Hands out innovation numbers and hidden node ids, so that the same
+/// structural mutation gets the same numbers in every genome it happens to.
+#[derive(Debug, Clone, Default)]
+pub struct Innovations {
+    next_innovation: usize,
+    next_node: usize,
+    connections: HashMap<(usize, usize), usize>,
+    /// Hidden node created by splitting the connection with this innovation
+    splits: HashMap<usize, usize>,
+}
+
+impl Innovations {
+    /// Picks up numbering where `genomes` left off, remembering their
+    /// connections and which connections their hidden nodes split.
+    pub fn new<'a>(genomes: impl IntoIterator<Item = &'a NeatGenome>) -> Self {
+        let genomes: Vec<&NeatGenome> = genomes.into_iter().collect();
+        let mut innovations = Self::default();
+
+        for genome in &genomes {
+            for node in &genome.nodes {
+                innovations.next_node = innovations.next_node.max(node.id + 1);
+            }
+            for connection in &genome.connections {
+                innovations
+                    .connections
+                    .insert((connection.from, connection.to), connection.innovation);
+                innovations.next_innovation =
+                    innovations.next_innovation.max(connection.innovation + 1);
+            }
+        }
+
+        // the halves of a split are the first connections into and out of
+        // the new node, anything else was added to it later
+        for genome in &genomes {
+            for node in genome.nodes.iter().filter(|n| n.kind == NodeKind::Hidden) {
+                let incoming = genome.connections.iter().find(|c| c.to == node.id);
+                let outgoing = genome.connections.iter().find(|c| c.from == node.id);
+                if let (Some(incoming), Some(outgoing)) = (incoming, outgoing)
+                    && let Some(&split) = innovations.connections.get(&(incoming.from, outgoing.to))
+                {
+                    innovations.splits.entry(split).or_insert(node.id);
+                }
+            }
+        }
+
+        innovations
+    }
+
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            self.next_innovation += 1;
+            self.next_innovation - 1
+        })
+    }
+
+    fn split(&mut self, innovation: usize) -> usize {
+        *self.splits.entry(innovation).or_insert_with(|| {
+            self.next_node += 1;
+            self.next_node - 1
+        })
+    }
+}
+
+/// Structural and weight mutations applied to a [`NeatGenome`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeatMutation {
+    /// Probability of perturbing each weight and bias
+    pub weight_chance: f32,
+    /// Standard deviation of that perturbation
+    pub weight_sigma: f32,
+    pub add_connection_chance: f32,
+    pub add_node_chance: f32,
+}
+
+impl Default for NeatMutation {
+    fn default() -> Self {
+        Self {
+            weight_chance: 0.1,
+            weight_sigma: 0.5,
+            add_connection_chance: 0.05,
+            add_node_chance: 0.03,
+        }
+    }
+}
+
+impl NeatMutation {
+    pub fn mutate(
+        &self,
+        rng: &mut dyn RngCore,
+        genome: &mut NeatGenome,
+        innovations: &mut Innovations,
+    ) {
+        let mut perturb = |value: &mut f32| {
+            if rng.random_bool(self.weight_chance as f64) {
+                *value += self.weight_sigma * rng.sample::<f32, _>(StandardNormal);
+            }
+        };
+
+        for connection in &mut genome.connections {
+            perturb(&mut connection.weight);
+        }
+        for node in &mut genome.nodes {
+            if node.kind != NodeKind::Input {
+                perturb(&mut node.bias);
+            }
+        }
+
+        if rng.random_bool(self.add_connection_chance as f64) {
+            genome.add_connection(rng, innovations);
+        }
+        if rng.random_bool(self.add_node_chance as f64) {
+            genome.add_node(rng, innovations);
+        }
+    }
+}
+
+/// Network built from a [`NeatGenome`], evaluating its nodes in
+/// topological order
+#[derive(Debug, Clone)]
+pub struct NeatNetwork {
+    genome: NeatGenome,
+    /// Indices of the hidden and output nodes, every node after the nodes
+    /// feeding into it
+    order: Vec<usize>,
+    /// Enabled connections into every node, as `(node index, weight)`
+    incoming: Vec<Vec<(usize, f32)>>,
+}
+
+impl NeatNetwork {
+    pub fn new(genome: NeatGenome) -> Self {
+        let index: HashMap<usize, usize> = genome
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+
+        let mut incoming = vec![Vec::new(); genome.nodes.len()];
+        let mut outgoing = vec![Vec::new(); genome.nodes.len()];
+        for connection in genome.connections.iter().filter(|c| c.enabled) {
+            let (from, to) = (index[&connection.from], index[&connection.to]);
+            incoming[to].push((from, connection.weight));
+            outgoing[from].push(to);
+        }
+
+        // Kahn's algorithm, inputs are ready from the start
+        let mut missing: Vec<usize> = incoming.iter().map(Vec::len).collect();
+        let mut ready: VecDeque<usize> = (0..genome.nodes.len())
+            .filter(|&node| missing[node] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(genome.nodes.len());
+
+        while let Some(node) = ready.pop_front() {
+            if genome.nodes[node].kind != NodeKind::Input {
+                order.push(node);
+            }
+            for &next in &outgoing[node] {
+                missing[next] -= 1;
+                if missing[next] == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+        assert_eq!(
+            order.len(),
+            genome.nodes.len() - genome.inputs,
+            "got a genome with a cycle"
+        );
+
+        Self {
+            genome,
+            order,
+            incoming,
+        }
+    }
+
+    pub fn genome(&self) -> &NeatGenome {
+        &self.genome
+    }
+
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.propagate_into(&inputs, &mut Vec::new()).to_vec()
+    }
+
+    /// Computes the value of every node into `values`, reusing its
+    /// allocation, and returns the outputs.
+    pub fn propagate_into<'v>(&self, inputs: &[f32], values: &'v mut Vec<f32>) -> &'v [f32] {
+        let genome = &self.genome;
+        assert_eq!(inputs.len(), genome.inputs);
+
+        values.clear();
+        values.extend_from_slice(inputs);
+        values.resize(genome.nodes.len(), 0.0);
+
+        for &node in &self.order {
+            let sum: f32 = self.incoming[node]
+                .iter()
+                .map(|&(from, weight)| weight * values[from])
+                .sum();
+            values[node] = genome.activation.apply(genome.nodes[node].bias + sum);
+        }
+
+        &values[genome.inputs..genome.inputs + genome.outputs]
+    }
+}
+
+/// [`NeatNetwork`] driving an agent, its genes being the weights and biases
+/// of its current topology
+#[derive(Debug, Clone)]
+pub struct NeatController {
+    network: NeatNetwork,
+    values: Vec<f32>,
+}
+
+impl NeatController {
+    pub fn new(network: NeatNetwork) -> Self {
+        Self {
+            network,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl Controller for NeatController {
+    fn inputs(&self) -> usize {
+        self.network.genome.inputs
+    }
+
+    fn outputs(&self) -> usize {
+        self.network.genome.outputs
+    }
+
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        self.network.propagate_into(inputs, &mut self.values)
+    }
+
+    fn to_genes(&self) -> Vec<f32> {
+        self.network.genome.weights().collect()
+    }
+
+    fn with_genes(&self, genes: &[f32]) -> Result<Box<dyn Controller>, Error> {
+        let genome = self.network.genome.with_weights(genes)?;
+        Ok(Box::new(Self::new(NeatNetwork::new(genome))))
+    }
+
+    fn neat(&self) -> Option<&NeatGenome> {
+        Some(&self.network.genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn genome(rng: &mut ChaCha8Rng) -> NeatGenome {
+        NeatGenome::minimal(rng, 2, 1, Activation::Identity)
+    }
+
+    #[test]
+    fn minimal_genome_is_fully_connected() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genome = NeatGenome::minimal(&mut rng, 3, 2, Activation::Tanh);
+
+        assert_eq!(genome.nodes.len(), 5);
+        assert_eq!(
+            genome
+                .connections
+                .iter()
+                .map(|c| (c.innovation, c.from, c.to))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 0, 3),
+                (1, 0, 4),
+                (2, 1, 3),
+                (3, 1, 4),
+                (4, 2, 3),
+                (5, 2, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn add_node_keeps_the_function() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut genome = genome(&mut rng);
+        let mut innovations = Innovations::new([&genome]);
+        let before = NeatNetwork::new(genome.clone()).propagate(vec![0.5, -0.25]);
+
+        assert!(genome.add_node(&mut rng, &mut innovations));
+
+        // identity activation and a zero bias make the split transparent
+        assert_eq!(genome.nodes.len(), 4);
+        assert_eq!(genome.nodes[3].kind, NodeKind::Hidden);
+        assert_eq!(genome.connections.iter().filter(|c| !c.enabled).count(), 1);
+        assert_relative_eq!(
+            NeatNetwork::new(genome)
+                .propagate(vec![0.5, -0.25])
+                .as_slice(),
+            before.as_slice()
+        );
+    }
+
+    #[test]
+    fn same_mutation_gets_same_innovation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut a = genome(&mut rng);
+        let mut innovations = Innovations::new([&a]);
+        let mut b = a.clone();
+
+        a.add_node(&mut rng, &mut innovations);
+        let split = a
+            .connections
+            .iter()
+            .find(|c| !c.enabled)
+            .unwrap()
+            .innovation;
+        let index = b
+            .connections
+            .iter()
+            .position(|c| c.innovation == split)
+            .unwrap();
+        // make `b` split the same connection
+        for (i, connection) in b.connections.iter_mut().enumerate() {
+            connection.enabled = i == index;
+        }
+        b.add_node(&mut rng, &mut innovations);
+
+        let innovations = |genome: &NeatGenome| {
+            genome
+                .connections
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(innovations(&a), innovations(&b));
+        assert_eq!(a.nodes[3].id, b.nodes[3].id);
+    }
+
+    #[test]
+    fn rebuilt_innovations_remember_splits() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut a = genome(&mut rng);
+        let mut b = a.clone();
+        a.add_node(&mut rng, &mut Innovations::new([&a]));
+
+        let split = a
+            .connections
+            .iter()
+            .find(|c| !c.enabled)
+            .unwrap()
+            .innovation;
+        let mut innovations = Innovations::new([&a, &b]);
+        // make `b` split the same connection
+        for connection in &mut b.connections {
+            connection.enabled = connection.innovation == split;
+        }
+        b.add_node(&mut rng, &mut innovations);
+
+        assert_eq!(a.nodes[3].id, b.nodes[3].id);
+        assert_eq!(
+            a.connections
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>(),
+            b.connections
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn add_connection_stays_acyclic() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut genome = NeatGenome::minimal(&mut rng, 3, 2, Activation::Tanh);
+        let mut innovations = Innovations::new([&genome]);
+
+        for _ in 0..30 {
+            genome.add_node(&mut rng, &mut innovations);
+            genome.add_connection(&mut rng, &mut innovations);
+        }
+
+        // building the network panics on cycles
+        let network = NeatNetwork::new(genome);
+        assert_eq!(network.propagate(vec![0.1, 0.2, 0.3]).len(), 2);
+    }
+
+    #[test]
+    fn crossover_inherits_structure_of_fitter_parent() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut fitter = genome(&mut rng);
+        let mut innovations = Innovations::new([&fitter]);
+        let other = fitter.clone();
+        fitter.add_node(&mut rng, &mut innovations);
+
+        let child = NeatGenome::crossover(&mut rng, &fitter, &other);
+
+        assert_eq!(child.nodes.len(), fitter.nodes.len());
+        assert_eq!(
+            child
+                .connections
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>(),
+            fitter
+                .connections
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>()
+        );
+        assert_relative_eq!(fitter.compatibility(&fitter), 0.0);
+        assert_relative_eq!(fitter.compatibility(&other), 2.0 / 4.0);
+    }
+
+    #[test]
+    fn controller_genes_round_trip() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let controller = NeatController::new(NeatNetwork::new(genome(&mut rng)));
+
+        let mut rebuilt = controller.with_genes(&[1.0, 2.0, 0.5]).unwrap();
+
+        assert_eq!(rebuilt.to_genes(), vec![1.0, 2.0, 0.5]);
+        assert_relative_eq!(rebuilt.propagate(&[1.0, 1.0])[0], 3.5);
+        assert!(controller.with_genes(&[1.0]).is_err());
+    }
+}
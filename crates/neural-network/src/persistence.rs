@@ -0,0 +1,458 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Activation, Error, LayerKind, LayerTopology, Network};
+
+/// Version written into every saved network, bumped on incompatible changes
+///
+/// - 1: dense layers only
+/// - 2: adds the layer kind, files without it load as dense layers
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Leading bytes of the binary format, used to tell it apart from JSON on load
+const MAGIC: &[u8; 4] = b"EVNN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable, produced with `serde_json`
+    Json,
+    /// Compact little-endian encoding, see [`Network::to_bytes`]
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The data is neither JSON nor starts with the binary magic bytes
+    UnknownFormat,
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+    /// The binary data ended before the network was fully read
+    Truncated,
+    /// The binary data continues after the network was fully read
+    TrailingBytes,
+    UnknownActivation(u8),
+    UnknownLayerKind(u8),
+    /// The topology and weights read do not make up a valid network
+    Network(Error),
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "i/o error: {err}"),
+            Self::Json(err) => write!(f, "invalid json: {err}"),
+            Self::UnknownFormat => write!(f, "unknown network file format"),
+            Self::UnsupportedVersion { found, supported } => write!(
+                f,
+                "unsupported format version {found} (supported 1 to {supported})"
+            ),
+            Self::Truncated => write!(f, "network data is truncated"),
+            Self::TrailingBytes => write!(f, "unexpected bytes after network data"),
+            Self::UnknownActivation(tag) => write!(f, "unknown activation tag {tag}"),
+            Self::UnknownLayerKind(tag) => write!(f, "unknown layer kind tag {tag}"),
+            Self::Network(err) => write!(f, "invalid network: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PersistenceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::Network(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistenceError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Error> for PersistenceError {
+    fn from(err: Error) -> Self {
+        Self::Network(err)
+    }
+}
+
+impl From<serde_json::Error> for PersistenceError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Self-describing form of a [`Network`]: everything needed to rebuild it
+/// without knowing its topology up front
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SerializedNetwork {
+    version: u32,
+    topology: Vec<LayerTopology>,
+    weights: Vec<f32>,
+}
+
+impl From<Network> for SerializedNetwork {
+    fn from(network: Network) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            topology: network.topology(),
+            weights: network.weights().collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedNetwork> for Network {
+    type Error = PersistenceError;
+
+    fn try_from(serialized: SerializedNetwork) -> Result<Self, Self::Error> {
+        check_version(serialized.version)?;
+
+        Ok(Network::try_from_weights(
+            &serialized.topology,
+            serialized.weights,
+        )?)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), PersistenceError> {
+    if !(1..=FORMAT_VERSION).contains(&version) {
+        Err(PersistenceError::UnsupportedVersion {
+            found: version,
+            supported: FORMAT_VERSION,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+impl Network {
+    /// Writes the network to `path`, including its topology and activations.
+    pub fn save(&self, path: impl AsRef<Path>, format: Format) -> Result<(), PersistenceError> {
+        let bytes = match format {
+            Format::Json => serde_json::to_vec_pretty(self)?,
+            Format::Binary => self.to_bytes(),
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Reads a network written by [`Network::save`], detecting the format
+    /// from the file contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PersistenceError> {
+        let bytes = fs::read(path)?;
+
+        if bytes.starts_with(MAGIC) {
+            Self::from_bytes(&bytes)
+        } else if bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+            // not straight into a network, whose invalid weights would
+            // only show as a json error
+            serde_json::from_slice::<SerializedNetwork>(&bytes)?.try_into()
+        } else {
+            Err(PersistenceError::UnknownFormat)
+        }
+    }
+
+    /// Binary layout (all integers and floats little-endian):
+    /// - magic `EVNN`
+    /// - `u32` format version
+    /// - `u32` layer count, then per layer a `u32` neuron count and an
+    ///   activation tag byte (leaky ReLU is followed by its `f32` alpha),
+    ///   followed by a layer kind tag byte since version 2
+    /// - `u32` weight count, then the weights as `f32`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let topology = self.topology();
+        let weights: Vec<f32> = self.weights().collect();
+
+        let mut bytes = Vec::with_capacity(16 + topology.len() * 9 + weights.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(topology.len() as u32).to_le_bytes());
+        for layer in &topology {
+            bytes.extend_from_slice(&(layer.neurons as u32).to_le_bytes());
+            match layer.activation {
+                Activation::Relu => bytes.push(0),
+                Activation::LeakyRelu { alpha } => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&alpha.to_le_bytes());
+                }
+                Activation::Sigmoid => bytes.push(2),
+                Activation::Tanh => bytes.push(3),
+                Activation::Identity => bytes.push(4),
+                Activation::Softsign => bytes.push(5),
+            }
+            bytes.push(match layer.kind {
+                LayerKind::Dense => 0,
+                LayerKind::Elman => 1,
+                LayerKind::Gru => 2,
+            });
+        }
+        bytes.extend_from_slice(&(weights.len() as u32).to_le_bytes());
+        for weight in weights {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(PersistenceError::UnknownFormat);
+        }
+        let version = reader.u32()?;
+        check_version(version)?;
+
+        let layers = reader.u32()? as usize;
+        let topology = (0..layers)
+            .map(|_| {
+                let neurons = reader.u32()? as usize;
+                let activation = match reader.u8()? {
+                    0 => Activation::Relu,
+                    1 => Activation::LeakyRelu {
+                        alpha: reader.f32()?,
+                    },
+                    2 => Activation::Sigmoid,
+                    3 => Activation::Tanh,
+                    4 => Activation::Identity,
+                    5 => Activation::Softsign,
+                    tag => return Err(PersistenceError::UnknownActivation(tag)),
+                };
+                let kind = if version < 2 {
+                    LayerKind::Dense
+                } else {
+                    match reader.u8()? {
+                        0 => LayerKind::Dense,
+                        1 => LayerKind::Elman,
+                        2 => LayerKind::Gru,
+                        tag => return Err(PersistenceError::UnknownLayerKind(tag)),
+                    }
+                };
+                Ok(LayerTopology {
+                    neurons,
+                    activation,
+                    kind,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let count = reader.u32()? as usize;
+        let weights = (0..count).map(|_| reader.f32()).collect::<Result<_, _>>()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(PersistenceError::TrailingBytes);
+        }
+
+        SerializedNetwork {
+            version,
+            topology,
+            weights,
+        }
+        .try_into()
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PersistenceError> {
+        if self.bytes.len() < len {
+            return Err(PersistenceError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, PersistenceError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, PersistenceError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, PersistenceError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn network() -> Network {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        Network::random(
+            &mut rng,
+            &[
+                LayerTopology {
+                    neurons: 3,
+                    activation: Activation::Identity,
+                    kind: LayerKind::Dense,
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::LeakyRelu { alpha: 0.01 },
+                    kind: LayerKind::Gru,
+                },
+                LayerTopology {
+                    neurons: 1,
+                    activation: Activation::Tanh,
+                    kind: LayerKind::Dense,
+                },
+            ],
+        )
+    }
+
+    fn assert_same(actual: &Network, expected: &Network) {
+        assert_eq!(actual.topology(), expected.topology());
+        approx::assert_relative_eq!(
+            actual.weights().collect::<Vec<_>>().as_slice(),
+            expected.weights().collect::<Vec<_>>().as_slice()
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let network = network();
+        let json = serde_json::to_string(&network).unwrap();
+
+        assert_same(&serde_json::from_str(&json).unwrap(), &network);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let network = network();
+
+        assert_same(&Network::from_bytes(&network.to_bytes()).unwrap(), &network);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let network = network();
+        let dir = std::env::temp_dir();
+
+        for (name, format) in [("nn.json", Format::Json), ("nn.bin", Format::Binary)] {
+            let path = dir.join(format!("{}-{name}", std::process::id()));
+            network.save(&path, format).unwrap();
+            let loaded = Network::load(&path);
+            fs::remove_file(&path).unwrap();
+
+            assert_same(&loaded.unwrap(), &network);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_binary() {
+        let bytes = network().to_bytes();
+
+        assert!(matches!(
+            Network::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(PersistenceError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut bytes = network().to_bytes();
+        bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            Network::from_bytes(&bytes),
+            Err(PersistenceError::UnsupportedVersion { found: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_version_0() {
+        let mut bytes = network().to_bytes();
+        bytes[4..8].copy_from_slice(&0u32.to_le_bytes());
+
+        assert!(matches!(
+            Network::from_bytes(&bytes),
+            Err(PersistenceError::UnsupportedVersion { found: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_weights() {
+        let json = r#"{
+            "version": 1,
+            "topology": [
+                { "neurons": 2, "activation": "Identity" },
+                { "neurons": 1, "activation": "Tanh" }
+            ],
+            "weights": [0.1, 0.2]
+        }"#;
+
+        assert!(matches!(
+            serde_json::from_str::<Network>(json),
+            Err(err) if err.to_string().contains("expected 3 weights, got 2")
+        ));
+
+        let path = std::env::temp_dir().join(format!("{}-mismatched.json", std::process::id()));
+        fs::write(&path, json).unwrap();
+        let loaded = Network::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            loaded,
+            Err(PersistenceError::Network(Error::WeightCountMismatch {
+                expected: 3,
+                actual: 2
+            }))
+        ));
+    }
+
+    #[test]
+    fn rejects_huge_neuron_counts() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        for kind in [0u8, 2] {
+            bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+            bytes.extend_from_slice(&[4, kind]);
+        }
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+
+        assert!(matches!(
+            Network::from_bytes(&bytes),
+            Err(PersistenceError::Network(Error::TooManyParameters))
+        ));
+    }
+
+    #[test]
+    fn loads_version_1_binary_as_dense() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        for (neurons, activation) in [(2u32, 4u8), (1, 3)] {
+            bytes.extend_from_slice(&neurons.to_le_bytes());
+            bytes.push(activation);
+        }
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        for weight in [0.5f32, -2.0, 1.0] {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+
+        let network = Network::from_bytes(&bytes).unwrap();
+
+        assert_eq!(network.topology()[1].kind, LayerKind::Dense);
+        approx::assert_relative_eq!(
+            network.propagate(vec![1.0, 0.5]).as_slice(),
+            [(-1.0f32).tanh()].as_ref()
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{Layer, LayerKind, Network};
+
+/// What [`Network::prune`] removes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PruneOptions {
+    /// Input and recurrent weights smaller in magnitude are set to zero,
+    /// which makes the network sparser but not smaller
+    pub min_weight: f32,
+    /// Whether to remove neurons of dense hidden layers that output zero
+    /// for every sample, e.g. ReLU neurons that never activate, or whose
+    /// outputs are not used by the next layer at all
+    pub remove_dead_neurons: bool,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            min_weight: 0.01,
+            remove_dead_neurons: true,
+        }
+    }
+}
+
+/// How much [`Network::prune`] removed and what it cost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PruneReport {
+    /// Weights set to zero for being below the threshold
+    pub weights_zeroed: usize,
+    pub neurons_removed: usize,
+    /// Number of weights and biases the network is made of, zero or not
+    pub parameters_before: usize,
+    /// Only lower than [`PruneReport::parameters_before`] when neurons were
+    /// removed, zeroed weights are still part of the network
+    pub parameters_after: usize,
+    /// Share of the input and recurrent weights of the pruned network that
+    /// are zero, from 0 to 1
+    pub sparsity: f32,
+    /// Largest difference between any output of the original and the
+    /// pruned network over the samples
+    pub max_deviation: f32,
+}
+
+impl Network {
+    /// Copy of the network without its dead neurons and with its small
+    /// weights set to zero, judged on `samples` of inputs.
+    ///
+    /// Only removing neurons makes the network smaller; zeroed weights are
+    /// still stored and multiplied, they only show in
+    /// [`PruneReport::sparsity`]. Removing dead neurons does not change the
+    /// outputs for these samples, zeroing small weights may: see
+    /// [`PruneReport::max_deviation`].
+    pub fn prune(&self, options: &PruneOptions, samples: &[Vec<f32>]) -> (Network, PruneReport) {
+        let mut pruned = self.clone();
+
+        let mut weights_zeroed = 0;
+        for layer in &mut pruned.layers {
+            for weight in layer.weights.iter_mut().chain(&mut layer.recurrent) {
+                if *weight != 0.0 && weight.abs() < options.min_weight {
+                    *weight = 0.0;
+                    weights_zeroed += 1;
+                }
+            }
+        }
+
+        let mut neurons_removed = 0;
+        if options.remove_dead_neurons {
+            let silent = pruned.silent_neurons(samples);
+
+            for index in 0..pruned.layers.len() - 1 {
+                if pruned.layers[index].kind != LayerKind::Dense {
+                    continue;
+                }
+
+                // from the back, so that earlier indices stay valid
+                for neuron in (0..pruned.layers[index].output_size).rev() {
+                    let (layer, next) = pruned.layers.split_at_mut(index + 1);
+                    let (layer, next) = (&mut layer[index], &mut next[0]);
+
+                    let dead = silent[index][neuron] || !next.uses_input(neuron);
+                    if dead && layer.output_size > 1 {
+                        layer.remove_output(neuron);
+                        next.remove_input(neuron);
+                        neurons_removed += 1;
+                    }
+                }
+            }
+        }
+
+        let max_deviation = samples
+            .iter()
+            .flat_map(|inputs| {
+                self.propagate(inputs.clone())
+                    .into_iter()
+                    .zip(pruned.propagate(inputs.clone()))
+                    .map(|(a, b)| (a - b).abs())
+            })
+            .fold(0.0, f32::max);
+
+        let (zeros, weights) = pruned
+            .layers
+            .iter()
+            .flat_map(|layer| layer.weights.iter().chain(&layer.recurrent))
+            .fold((0, 0), |(zeros, weights), &weight| {
+                (zeros + usize::from(weight == 0.0), weights + 1)
+            });
+
+        let report = PruneReport {
+            weights_zeroed,
+            neurons_removed,
+            parameters_before: self.weight_count(),
+            parameters_after: pruned.weight_count(),
+            sparsity: zeros as f32 / weights as f32,
+            max_deviation,
+        };
+        (pruned, report)
+    }
+
+    /// For every layer and neuron, whether it outputs zero for all samples
+    fn silent_neurons(&self, samples: &[Vec<f32>]) -> Vec<Vec<bool>> {
+        let mut silent: Vec<Vec<bool>> = self
+            .layers
+            .iter()
+            .map(|layer| vec![!samples.is_empty(); layer.output_size])
+            .collect();
+
+        for inputs in samples {
+            let trace = self.propagate_traced(inputs.clone());
+            for (silent, outputs) in silent.iter_mut().zip(&trace[1..]) {
+                for (silent, &output) in silent.iter_mut().zip(outputs) {
+                    *silent &= output == 0.0;
+                }
+            }
+        }
+
+        silent
+    }
+}
+
+impl Layer {
+    /// Whether any row has a non-zero weight for input `column`
+    fn uses_input(&self, column: usize) -> bool {
+        self.weights
+            .chunks_exact(self.input_size)
+            .any(|row| row[column] != 0.0)
+    }
+
+    /// Drops the row of output `neuron`, dense layers only
+    fn remove_output(&mut self, neuron: usize) {
+        assert_eq!(self.kind, LayerKind::Dense);
+
+        self.biases.remove(neuron);
+        self.weights
+            .drain(neuron * self.input_size..(neuron + 1) * self.input_size);
+        self.output_size -= 1;
+    }
+
+    /// Drops the weight of input `column` from every row
+    fn remove_input(&mut self, column: usize) {
+        let input_size = self.input_size;
+        let mut index = 0;
+        self.weights.retain(|_| {
+            index += 1;
+            (index - 1) % input_size != column
+        });
+        self.input_size -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{Activation, LayerTopology};
+
+    fn network() -> Network {
+        let topology = [
+            (2, Activation::Identity),
+            (3, Activation::Relu),
+            (1, Activation::Identity),
+        ]
+        .map(|(neurons, activation)| LayerTopology {
+            neurons,
+            activation,
+            kind: LayerKind::Dense,
+        });
+
+        // the second hidden neuron never activates for non-negative inputs,
+        // the third one is ignored by the output
+        #[rustfmt::skip]
+        let weights = [
+            0.1, 1.0, 0.005,
+            -5.0, 1.0, 1.0,
+            0.0, 0.5, 0.5,
+            0.0, 2.0, 3.0, 0.0,
+        ];
+        Network::from_weights(&topology, weights)
+    }
+
+    fn samples() -> Vec<Vec<f32>> {
+        vec![vec![0.0, 0.0], vec![1.0, 0.5], vec![2.0, 1.0]]
+    }
+
+    #[test]
+    fn removes_dead_neurons_without_changing_outputs() {
+        let options = PruneOptions {
+            min_weight: 0.0,
+            remove_dead_neurons: true,
+        };
+        let (pruned, report) = network().prune(&options, &samples());
+
+        assert_eq!(pruned.topology()[1].neurons, 1);
+        assert_eq!(report.neurons_removed, 2);
+        assert_eq!(report.weights_zeroed, 0);
+        assert_eq!(report.parameters_before, 13);
+        assert_eq!(report.parameters_after, 3 + 2);
+        assert_relative_eq!(report.max_deviation, 0.0);
+    }
+
+    #[test]
+    fn zeroes_small_weights() {
+        let options = PruneOptions {
+            min_weight: 0.01,
+            remove_dead_neurons: false,
+        };
+        let (pruned, report) = network().prune(&options, &samples());
+
+        assert_eq!(report.weights_zeroed, 1);
+        assert_eq!(report.parameters_after, report.parameters_before);
+        // the weight zeroed and the one that already was, out of 9
+        assert_relative_eq!(report.sparsity, 2.0 / 9.0);
+        assert_relative_eq!(pruned.layers[0].weights[1], 0.0);
+        // 2 * 0.005 * 1.0 for the last sample
+        assert_relative_eq!(report.max_deviation, 0.01, epsilon = 1e-6);
+    }
+}
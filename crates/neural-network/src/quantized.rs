@@ -0,0 +1,358 @@
+use std::mem;
+
+use crate::{Activation, LayerKind, Network, Scratch, State};
+
+/// Read-only copy of a [`Network`] with int8 weights, for cheaper inference
+/// in long headless runs.
+///
+/// Every layer keeps its input and recurrent weights as `i8`, with one
+/// scale for each per layer. Values multiplied by them are quantised to
+/// `i16` on the fly with a scale taken from their largest magnitude, the
+/// weighted sums are accumulated in `i64` and only the result is turned
+/// back into `f32` for the bias, the gates and the activation. Recurrent
+/// layers remember their previous outputs like those of the original
+/// network, see [`QuantizedNetwork::propagate_with_state`].
+///
+/// Genetic operators keep working on the `f32` network; quantise again
+/// after changing it.
+#[derive(Debug, Clone)]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct QuantizedLayer {
+    input_size: usize,
+    output_size: usize,
+    kind: LayerKind,
+    activation: Activation,
+    /// `[gate * output][input]`, multiplied by `scale` gives the original
+    /// weight
+    weights: Vec<i8>,
+    scale: f32,
+    /// `[gate * output][output]`, empty for dense layers
+    recurrent: Vec<i8>,
+    recurrent_scale: f32,
+    biases: Vec<f32>,
+}
+
+impl Network {
+    pub fn quantize(&self) -> QuantizedNetwork {
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                let (weights, scale) = quantize(&layer.weights);
+                let (recurrent, recurrent_scale) = quantize(&layer.recurrent);
+
+                QuantizedLayer {
+                    input_size: layer.input_size,
+                    output_size: layer.output_size,
+                    kind: layer.kind,
+                    activation: layer.activation,
+                    weights,
+                    scale,
+                    recurrent,
+                    recurrent_scale,
+                    biases: layer.biases.clone(),
+                }
+            })
+            .collect();
+
+        QuantizedNetwork { layers }
+    }
+}
+
+/// `weights` as `i8` and the scale turning them back into `f32`
+fn quantize(weights: &[f32]) -> (Vec<i8>, f32) {
+    let max = weights.iter().fold(0.0f32, |max, w| max.max(w.abs()));
+    let scale = if max > 0.0 { max / i8::MAX as f32 } else { 1.0 };
+
+    let weights = weights.iter().map(|w| (w / scale).round() as i8).collect();
+    (weights, scale)
+}
+
+impl QuantizedNetwork {
+    /// Recurrent layers behave as if their previous outputs were all zero,
+    /// like [`Network::propagate`].
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.propagate_into(&inputs, &mut Scratch::new()).to_vec()
+    }
+
+    /// Non-allocating counterpart of [`QuantizedNetwork::propagate`], see
+    /// [`Network::propagate_into`].
+    pub fn propagate_into<'s>(&self, inputs: &[f32], scratch: &'s mut Scratch) -> &'s [f32] {
+        self.run(inputs, None, scratch)
+    }
+
+    /// Like [`QuantizedNetwork::propagate_into`], but recurrent layers see
+    /// and update their previous outputs kept in `state`, created by
+    /// [`QuantizedNetwork::state`] or [`Network::state`] of the original
+    /// network.
+    pub fn propagate_with_state<'s>(
+        &self,
+        inputs: &[f32],
+        state: &mut State,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        assert_eq!(state.hidden.len(), self.layers.len());
+
+        self.run(inputs, Some(state), scratch)
+    }
+
+    pub fn state(&self) -> State {
+        State::new(self.layers.iter().map(|layer| {
+            if layer.kind.is_recurrent() {
+                layer.output_size
+            } else {
+                0
+            }
+        }))
+    }
+
+    fn run<'s>(
+        &self,
+        inputs: &[f32],
+        mut state: Option<&mut State>,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        let Scratch {
+            current,
+            next,
+            gates,
+            quantized,
+        } = scratch;
+
+        current.clear();
+        current.extend_from_slice(inputs);
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let hidden = match &mut state {
+                Some(state) if layer.kind.is_recurrent() => Some(&mut state.hidden[index]),
+                _ => None,
+            };
+
+            layer.forward(
+                current,
+                hidden.as_deref().map(Vec::as_slice),
+                next,
+                gates,
+                quantized,
+            );
+            if let Some(hidden) = hidden {
+                hidden.copy_from_slice(next);
+            }
+            mem::swap(current, next);
+        }
+
+        current
+    }
+
+    /// Largest difference between any output of this network and `network`,
+    /// usually the one it was quantised from, over `samples` of inputs
+    pub fn max_error(&self, network: &Network, samples: &[Vec<f32>]) -> f32 {
+        samples
+            .iter()
+            .flat_map(|inputs| {
+                self.propagate(inputs.clone())
+                    .into_iter()
+                    .zip(network.propagate(inputs.clone()))
+                    .map(|(a, b)| (a - b).abs())
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Memory taken by the weights, biases and scales, in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weights.len() + layer.recurrent.len() + 4 * (layer.biases.len() + 2))
+            .sum()
+    }
+}
+
+impl QuantizedLayer {
+    /// Same steps as the `f32` kernel, with quantised weighted sums
+    fn forward(
+        &self,
+        inputs: &[f32],
+        hidden: Option<&[f32]>,
+        outputs: &mut Vec<f32>,
+        gates: &mut Vec<f32>,
+        quantized: &mut Vec<i16>,
+    ) {
+        assert_eq!(inputs.len(), self.input_size);
+
+        let size = self.output_size;
+        gates.clear();
+        gates.extend_from_slice(&self.biases);
+        affine(&self.weights, self.scale, inputs, quantized, gates);
+
+        outputs.clear();
+        match self.kind {
+            LayerKind::Dense | LayerKind::Elman => {
+                if let Some(hidden) = hidden {
+                    affine(
+                        &self.recurrent,
+                        self.recurrent_scale,
+                        hidden,
+                        quantized,
+                        gates,
+                    );
+                }
+                outputs.extend(gates.iter().map(|&sum| self.activation.apply(sum)));
+            }
+
+            LayerKind::Gru => {
+                let (update_and_reset, candidate) = gates.split_at_mut(2 * size);
+                let (gate_weights, candidate_weights) = self.recurrent.split_at(2 * size * size);
+
+                if let Some(hidden) = hidden {
+                    affine(
+                        gate_weights,
+                        self.recurrent_scale,
+                        hidden,
+                        quantized,
+                        update_and_reset,
+                    );
+                }
+                for gate in update_and_reset.iter_mut() {
+                    *gate = Activation::Sigmoid.apply(*gate);
+                }
+
+                let (update, reset) = update_and_reset.split_at(size);
+                if let Some(hidden) = hidden {
+                    // reuse the outputs as storage for `reset * hidden`
+                    outputs.extend(reset.iter().zip(hidden).map(|(r, h)| r * h));
+                    affine(
+                        candidate_weights,
+                        self.recurrent_scale,
+                        outputs,
+                        quantized,
+                        candidate,
+                    );
+                    outputs.clear();
+                }
+
+                outputs.extend(update.iter().zip(candidate.iter()).enumerate().map(
+                    |(index, (z, candidate))| {
+                        let previous = hidden.map_or(0.0, |hidden| hidden[index]);
+                        (1.0 - z) * previous + z * self.activation.apply(*candidate)
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// `outputs[row] += Σ weights[row][column] * values[column]`, with `values`
+/// quantised into `quantized` first
+fn affine(
+    weights: &[i8],
+    scale: f32,
+    values: &[f32],
+    quantized: &mut Vec<i16>,
+    outputs: &mut [f32],
+) {
+    let max = values.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+    let value_scale = if max > 0.0 {
+        max / i16::MAX as f32
+    } else {
+        1.0
+    };
+    quantized.clear();
+    quantized.extend(values.iter().map(|x| (x / value_scale).round() as i16));
+
+    let scale = scale * value_scale;
+    for (output, row) in outputs.iter_mut().zip(weights.chunks_exact(values.len())) {
+        let sum: i64 = row
+            .iter()
+            .zip(quantized.iter())
+            .map(|(&w, &x)| w as i64 * x as i64)
+            .sum();
+        *output += sum as f32 * scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::LayerTopology;
+
+    #[test]
+    fn stays_close_to_f32() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        for hidden in [LayerKind::Dense, LayerKind::Elman, LayerKind::Gru] {
+            let topology = [
+                (9, Activation::Identity, LayerKind::Dense),
+                (18, Activation::Relu, hidden),
+                (2, Activation::Tanh, LayerKind::Dense),
+            ]
+            .map(|(neurons, activation, kind)| LayerTopology {
+                neurons,
+                activation,
+                kind,
+            });
+            let network = Network::random(&mut rng, &topology);
+            let samples: Vec<Vec<f32>> = (0..20)
+                .map(|_| (0..9).map(|_| rng.random_range(0.0..=1.0)).collect())
+                .collect();
+
+            let quantized = network.quantize();
+
+            assert!(quantized.max_error(&network, &samples) < 0.05);
+        }
+    }
+
+    #[test]
+    fn zero_inputs_and_weights() {
+        let topology = [2, 1].map(|neurons| LayerTopology {
+            neurons,
+            activation: Activation::Identity,
+            kind: LayerKind::Dense,
+        });
+        let quantized = Network::from_weights(&topology, [0.5, 0.0, 0.0]).quantize();
+
+        assert_eq!(quantized.propagate(vec![0.0, 0.0]), vec![0.5]);
+        assert_eq!(quantized.size_in_bytes(), 2 + 4 * 3);
+    }
+
+    #[test]
+    fn remembers_like_f32() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        for hidden in [LayerKind::Elman, LayerKind::Gru] {
+            let topology = [
+                (4, Activation::Identity, LayerKind::Dense),
+                (8, Activation::Tanh, hidden),
+                (2, Activation::Tanh, LayerKind::Dense),
+            ]
+            .map(|(neurons, activation, kind)| LayerTopology {
+                neurons,
+                activation,
+                kind,
+            });
+            let network = Network::random(&mut rng, &topology);
+            let quantized = network.quantize();
+            let (mut state, mut quantized_state) = (network.state(), quantized.state());
+            let (mut scratch, mut quantized_scratch) = (Scratch::new(), Scratch::new());
+
+            for _ in 0..10 {
+                let inputs: Vec<f32> = (0..4).map(|_| rng.random_range(-1.0..=1.0)).collect();
+
+                let expected = network.propagate_with_state(&inputs, &mut state, &mut scratch);
+                let actual = quantized.propagate_with_state(
+                    &inputs,
+                    &mut quantized_state,
+                    &mut quantized_scratch,
+                );
+
+                for (actual, expected) in actual.iter().zip(expected) {
+                    assert!((actual - expected).abs() < 0.05);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::fmt::Write;
+
+use crate::{Layer, LayerKind, Network};
+
+const POSITIVE: &str = "#2ca02c";
+const NEGATIVE: &str = "#d62728";
+
+/// Pixel layout of [`Network::to_svg`]
+const COLUMN_SPACING: f32 = 160.0;
+const ROW_SPACING: f32 = 40.0;
+const MARGIN: f32 = 40.0;
+const RADIUS: f32 = 12.0;
+
+/// What [`Network::to_dot`] and [`Network::to_svg`] draw
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// Connections whose weight is smaller in magnitude are left out
+    pub min_weight: f32,
+    /// Whether to draw the connections of recurrent layers to themselves
+    pub recurrent: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            min_weight: 0.0,
+            recurrent: true,
+        }
+    }
+}
+
+/// Connection between two neurons, `from` being in the previous column for
+/// forward edges and in the same column for recurrent ones
+struct Edge {
+    column: usize,
+    from: usize,
+    to: usize,
+    weight: f32,
+    recurrent: bool,
+}
+
+impl Edge {
+    fn color(&self) -> &'static str {
+        if self.weight >= 0.0 {
+            POSITIVE
+        } else {
+            NEGATIVE
+        }
+    }
+
+    /// Stroke width between 0.5 and 3, proportional to the magnitude
+    fn width(&self, max_weight: f32) -> f32 {
+        0.5 + 2.5 * self.weight.abs() / max_weight.max(f32::EPSILON)
+    }
+}
+
+/// Rows whose weights drive the outputs of a layer: all of them, except for
+/// GRUs where only the candidate output is drawn.
+fn output_rows(layer: &Layer) -> usize {
+    match layer.kind {
+        LayerKind::Dense | LayerKind::Elman => 0,
+        LayerKind::Gru => 2 * layer.output_size,
+    }
+}
+
+impl Network {
+    fn edges(&self, options: &RenderOptions) -> Vec<Edge> {
+        let mut edges = Vec::new();
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let first = output_rows(layer);
+
+            for to in 0..layer.output_size {
+                let row = first + to;
+                let weights = &layer.weights[row * layer.input_size..][..layer.input_size];
+                edges.extend(weights.iter().enumerate().map(|(from, &weight)| Edge {
+                    column: index + 1,
+                    from,
+                    to,
+                    weight,
+                    recurrent: false,
+                }));
+
+                if options.recurrent && layer.kind.is_recurrent() {
+                    let size = layer.output_size;
+                    let weights = &layer.recurrent[row * size..][..size];
+                    edges.extend(weights.iter().enumerate().map(|(from, &weight)| Edge {
+                        column: index + 1,
+                        from,
+                        to,
+                        weight,
+                        recurrent: true,
+                    }));
+                }
+            }
+        }
+
+        edges.retain(|edge| edge.weight.abs() >= options.min_weight);
+        edges
+    }
+
+    /// Sizes of every column, inputs first
+    fn columns(&self) -> Vec<usize> {
+        self.topology().iter().map(|layer| layer.neurons).collect()
+    }
+
+    /// Graphviz graph with one node per neuron, labelled with its bias, and
+    /// one edge per connection, green when positive and red when negative,
+    /// thicker the larger its weight. Recurrent connections are dashed.
+    pub fn to_dot(&self, options: &RenderOptions) -> String {
+        let edges = self.edges(options);
+        let max_weight = edges
+            .iter()
+            .map(|edge| edge.weight.abs())
+            .fold(0.0, f32::max);
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph network {{").unwrap();
+        writeln!(dot, "  rankdir=LR;").unwrap();
+        writeln!(dot, "  node [shape=circle, fontsize=10];").unwrap();
+
+        for (column, &size) in self.columns().iter().enumerate() {
+            writeln!(dot, "  subgraph cluster_{column} {{").unwrap();
+            match column.checked_sub(1).map(|index| &self.layers[index]) {
+                None => {
+                    writeln!(dot, "    label=\"input\";").unwrap();
+                    for neuron in 0..size {
+                        writeln!(dot, "    n{column}_{neuron} [label=\"x{neuron}\"];").unwrap();
+                    }
+                }
+                Some(layer) => {
+                    writeln!(
+                        dot,
+                        "    label=\"{:?} {:?}\";",
+                        layer.kind, layer.activation
+                    )
+                    .unwrap();
+                    for neuron in 0..size {
+                        let bias = layer.biases[output_rows(layer) + neuron];
+                        writeln!(dot, "    n{column}_{neuron} [label=\"{bias:.2}\"];").unwrap();
+                    }
+                }
+            }
+            writeln!(dot, "  }}").unwrap();
+        }
+
+        for edge in &edges {
+            let source = if edge.recurrent {
+                edge.column
+            } else {
+                edge.column - 1
+            };
+            write!(
+                dot,
+                "  n{source}_{} -> n{}_{} [color=\"{}\", penwidth={:.2}, tooltip=\"{:.3}\"",
+                edge.from,
+                edge.column,
+                edge.to,
+                edge.color(),
+                edge.width(max_weight),
+                edge.weight,
+            )
+            .unwrap();
+            if edge.recurrent {
+                write!(dot, ", style=dashed, constraint=false").unwrap();
+            }
+            writeln!(dot, "];").unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Self-contained SVG drawing of the same graph as [`Network::to_dot`],
+    /// with the layers laid out from left to right.
+    pub fn to_svg(&self, options: &RenderOptions) -> String {
+        let columns = self.columns();
+        let edges = self.edges(options);
+        let max_weight = edges
+            .iter()
+            .map(|edge| edge.weight.abs())
+            .fold(0.0, f32::max);
+
+        let tallest = columns.iter().copied().max().unwrap_or(0) as f32;
+        let width = 2.0 * MARGIN + (columns.len() - 1) as f32 * COLUMN_SPACING + 2.0 * RADIUS;
+        let height = 2.0 * MARGIN + tallest * ROW_SPACING;
+
+        // neurons are centred vertically within their column
+        let position = |column: usize, neuron: usize| {
+            let offset = (tallest - columns[column] as f32) * ROW_SPACING / 2.0;
+            (
+                MARGIN + RADIUS + column as f32 * COLUMN_SPACING,
+                MARGIN + offset + (neuron as f32 + 0.5) * ROW_SPACING,
+            )
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"0 0 {width:.0} {height:.0}\">"
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+        )
+        .unwrap();
+
+        for edge in &edges {
+            let (x2, y2) = position(edge.column, edge.to);
+            let (color, stroke) = (edge.color(), edge.width(max_weight));
+
+            if edge.recurrent {
+                let (_, y1) = position(edge.column, edge.from);
+                let bulge = x2 + RADIUS + 30.0;
+                writeln!(
+                    svg,
+                    "  <path d=\"M {:.1} {y1:.1} C {bulge:.1} {:.1} {bulge:.1} {:.1} {:.1} {y2:.1}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{stroke:.2}\" stroke-dasharray=\"4 2\"/>",
+                    x2 + RADIUS,
+                    y1 - RADIUS,
+                    y2 + RADIUS,
+                    x2 + RADIUS,
+                )
+                .unwrap();
+            } else {
+                let (x1, y1) = position(edge.column - 1, edge.from);
+                writeln!(
+                    svg,
+                    "  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" stroke=\"{color}\" stroke-width=\"{stroke:.2}\"/>"
+                )
+                .unwrap();
+            }
+        }
+
+        for (column, &size) in columns.iter().enumerate() {
+            for neuron in 0..size {
+                let (x, y) = position(column, neuron);
+                writeln!(
+                    svg,
+                    "  <circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{RADIUS}\" fill=\"#f0f0f0\" stroke=\"black\"/>"
+                )
+                .unwrap();
+            }
+
+            let label = match column.checked_sub(1).map(|index| &self.layers[index]) {
+                None => "input".to_string(),
+                Some(layer) => format!("{:?} {:?}", layer.kind, layer.activation),
+            };
+            let (x, _) = position(column, 0);
+            writeln!(
+                svg,
+                "  <text x=\"{x:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"11\" text-anchor=\"middle\">{label}</text>",
+                height - MARGIN / 2.0
+            )
+            .unwrap();
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Activation, LayerTopology};
+
+    use super::*;
+
+    fn network() -> Network {
+        let topology = [
+            (2, LayerKind::Dense),
+            (2, LayerKind::Elman),
+            (1, LayerKind::Dense),
+        ]
+        .map(|(neurons, kind)| LayerTopology {
+            neurons,
+            activation: Activation::Tanh,
+            kind,
+        });
+
+        // per row: bias, inputs, recurrent weights
+        #[rustfmt::skip]
+        let weights = [
+            0.1, 0.5, -0.5, 0.9, 0.0,
+            0.2, 0.01, 1.0, -0.3, 0.6,
+            0.0, -2.0, 0.02,
+        ];
+        Network::from_weights(&topology, weights)
+    }
+
+    #[test]
+    fn dot_has_an_edge_per_connection() {
+        let dot = network().to_dot(&RenderOptions::default());
+
+        assert!(dot.starts_with("digraph network {"));
+        assert_eq!(dot.matches("->").count(), 4 + 4 + 2);
+        assert_eq!(dot.matches("style=dashed").count(), 4);
+        assert!(dot.contains(&format!(
+            "n1_0 -> n2_0 [color=\"{NEGATIVE}\", penwidth=3.00"
+        )));
+        assert!(dot.contains("label=\"Elman Tanh\""));
+    }
+
+    #[test]
+    fn dot_prunes_small_weights() {
+        let options = RenderOptions {
+            min_weight: 0.1,
+            recurrent: false,
+        };
+
+        assert_eq!(network().to_dot(&options).matches("->").count(), 3 + 1);
+    }
+
+    #[test]
+    fn svg_draws_every_neuron_and_edge() {
+        let svg = network().to_svg(&RenderOptions::default());
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert_eq!(svg.matches("<line").count(), 6);
+        assert_eq!(svg.matches("<path").count(), 4);
+    }
+}
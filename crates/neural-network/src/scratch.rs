@@ -0,0 +1,129 @@
+use std::mem;
+
+use crate::{Network, State};
+
+/// Reusable buffers for [`Network::propagate_into`] and
+/// [`NetworkBatch::propagate_into`](crate::NetworkBatch::propagate_into), so
+/// that propagating the same networks over and over does not allocate once
+/// the buffers have grown.
+#[derive(Debug, Clone, Default)]
+pub struct Scratch {
+    pub(crate) current: Vec<f32>,
+    pub(crate) next: Vec<f32>,
+    /// Intermediate gate values of GRU layers
+    pub(crate) gates: Vec<f32>,
+    /// Values quantised by [`QuantizedNetwork`](crate::QuantizedNetwork)
+    pub(crate) quantized: Vec<i16>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preallocates buffers wide enough for every layer of `network`.
+    pub fn for_network(network: &Network) -> Self {
+        let width = network
+            .layers
+            .iter()
+            .map(|layer| layer.output_size())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            current: Vec::with_capacity(width),
+            next: Vec::with_capacity(width),
+            gates: Vec::with_capacity(3 * width),
+            quantized: Vec::new(),
+        }
+    }
+}
+
+impl Network {
+    /// Non-allocating counterpart of [`Network::propagate`], the returned
+    /// outputs live inside `scratch` until its next use.
+    ///
+    /// Recurrent layers behave as if their previous outputs were all zero,
+    /// use [`Network::propagate_with_state`] to let them remember.
+    pub fn propagate_into<'s>(&self, inputs: &[f32], scratch: &'s mut Scratch) -> &'s [f32] {
+        self.run(inputs, None, scratch)
+    }
+
+    /// Like [`Network::propagate_into`], but recurrent layers see and update
+    /// their previous outputs kept in `state`.
+    pub fn propagate_with_state<'s>(
+        &self,
+        inputs: &[f32],
+        state: &mut State,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        assert_eq!(state.hidden.len(), self.layers.len());
+
+        self.run(inputs, Some(state), scratch)
+    }
+
+    fn run<'s>(
+        &self,
+        inputs: &[f32],
+        mut state: Option<&mut State>,
+        scratch: &'s mut Scratch,
+    ) -> &'s [f32] {
+        assert!(!self.layers.is_empty(), "got an empty network");
+
+        let Scratch {
+            current,
+            next,
+            gates,
+            ..
+        } = scratch;
+
+        current.clear();
+        current.extend_from_slice(inputs);
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let hidden = match &mut state {
+                Some(state) if layer.kind.is_recurrent() => Some(&mut state.hidden[index]),
+                _ => None,
+            };
+
+            layer.forward(current, hidden.as_deref().map(Vec::as_slice), next, gates);
+            if let Some(hidden) = hidden {
+                hidden.copy_from_slice(next);
+            }
+            mem::swap(current, next);
+        }
+
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{Activation, LayerKind, LayerTopology};
+
+    use super::*;
+
+    #[test]
+    fn propagate_into_matches_propagate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(
+            &mut rng,
+            &[3, 5, 4, 2].map(|neurons| LayerTopology {
+                neurons,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+            }),
+        );
+        let mut scratch = Scratch::for_network(&network);
+
+        for inputs in [[0.1, 0.2, 0.3], [-1.0, 0.5, 0.0]] {
+            let expected = network.propagate(inputs.to_vec());
+            let actual = network.propagate_into(&inputs, &mut scratch);
+
+            approx::assert_relative_eq!(actual, expected.as_slice());
+        }
+    }
+}
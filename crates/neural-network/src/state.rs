@@ -0,0 +1,27 @@
+/// Previous outputs of every recurrent layer, owned by whoever runs the
+/// network so that one [`Network`](crate::Network) can drive many
+/// independent instances.
+///
+/// Created through [`Network::state`](crate::Network::state) or
+/// [`NetworkBatch::state`](crate::NetworkBatch::state); a fresh state is all
+/// zeros, as if nothing had been seen yet.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    /// Per layer, empty for layers without memory
+    pub(crate) hidden: Vec<Vec<f32>>,
+}
+
+impl State {
+    pub(crate) fn new(sizes: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            hidden: sizes.into_iter().map(|size| vec![0.0; size]).collect(),
+        }
+    }
+
+    /// Forgets everything seen so far
+    pub fn reset(&mut self) {
+        for hidden in &mut self.hidden {
+            hidden.fill(0.0);
+        }
+    }
+}
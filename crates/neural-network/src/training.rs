@@ -0,0 +1,934 @@
+use crate::{Activation, Error, Layer, LayerKind, Network, Scratch, heads::softmax};
+
+/// Keeps cross-entropy finite for outputs that saturate at 0 or 1
+const EPSILON: f32 = 1e-7;
+
+/// Error between the outputs of a network and the expected ones, averaged
+/// over the outputs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Loss {
+    #[default]
+    MeanSquaredError,
+    /// Binary cross-entropy, for outputs in (0, 1) such as sigmoid ones
+    CrossEntropy,
+    /// Categorical cross-entropy of the softmax of the outputs, summed
+    /// rather than averaged, for logits such as those read by a
+    /// [`Head::Discrete`](crate::Head::Discrete); targets are probabilities
+    /// summing up to one, e.g. one-hot choices
+    SoftmaxCrossEntropy,
+}
+
+impl Loss {
+    pub fn value(&self, outputs: &[f32], targets: &[f32]) -> f32 {
+        assert_eq!(outputs.len(), targets.len());
+
+        let n = outputs.len() as f32;
+        let pairs = outputs.iter().zip(targets);
+
+        match self {
+            Self::MeanSquaredError => {
+                pairs
+                    .map(|(output, target)| (output - target).powi(2))
+                    .sum::<f32>()
+                    / n
+            }
+            Self::CrossEntropy => {
+                pairs
+                    .map(|(&output, &target)| {
+                        let output = output.clamp(EPSILON, 1.0 - EPSILON);
+                        -(target * output.ln() + (1.0 - target) * (1.0 - output).ln())
+                    })
+                    .sum::<f32>()
+                    / n
+            }
+            Self::SoftmaxCrossEntropy => softmax(outputs)
+                .iter()
+                .zip(targets)
+                .map(|(probability, target)| -target * probability.max(EPSILON).ln())
+                .sum(),
+        }
+    }
+
+    /// Average loss of `network` over `samples` of inputs and expected
+    /// outputs. Recurrent layers behave as if their previous outputs were
+    /// all zero.
+    pub fn evaluate(
+        &self,
+        network: &Network,
+        samples: &[(Vec<f32>, Vec<f32>)],
+    ) -> Result<f32, Error> {
+        if samples.is_empty() {
+            return Err(Error::NoSamples);
+        }
+
+        let total: f32 = samples
+            .iter()
+            .map(|(inputs, targets)| self.value(&network.propagate(inputs.clone()), targets))
+            .sum();
+
+        Ok(total / samples.len() as f32)
+    }
+
+    /// Average loss of `network` over every step of `sequences`, each of
+    /// them propagated in order from an all-zero state
+    pub fn evaluate_sequences(
+        &self,
+        network: &Network,
+        sequences: &[Vec<(Vec<f32>, Vec<f32>)>],
+    ) -> Result<f32, Error> {
+        let mut scratch = Scratch::for_network(network);
+        let mut total = 0.0;
+        let mut count = 0;
+
+        for sequence in sequences {
+            let mut state = network.state();
+            for (inputs, targets) in sequence {
+                let outputs = network.propagate_with_state(inputs, &mut state, &mut scratch);
+                total += self.value(outputs, targets);
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            return Err(Error::NoSamples);
+        }
+        Ok(total / count as f32)
+    }
+
+    /// Derivative of [`Loss::value`] with respect to every output
+    fn gradient(&self, outputs: &[f32], targets: &[f32]) -> Vec<f32> {
+        let n = outputs.len() as f32;
+        let pairs = outputs.iter().zip(targets);
+
+        match self {
+            Self::MeanSquaredError => pairs
+                .map(|(output, target)| 2.0 * (output - target) / n)
+                .collect(),
+            Self::CrossEntropy => pairs
+                .map(|(&output, &target)| {
+                    let output = output.clamp(EPSILON, 1.0 - EPSILON);
+                    (output - target) / (output * (1.0 - output)) / n
+                })
+                .collect(),
+            Self::SoftmaxCrossEntropy => {
+                let total: f32 = targets.iter().sum();
+                softmax(outputs)
+                    .iter()
+                    .zip(targets)
+                    .map(|(probability, target)| probability * total - target)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// How [`Trainer`] turns gradients into parameter updates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Optimizer {
+    /// Plain stochastic gradient descent
+    Sgd { learning_rate: f32 },
+    /// Gradient descent that keeps moving in the direction of past updates
+    Momentum { learning_rate: f32, momentum: f32 },
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+impl Optimizer {
+    pub fn sgd(learning_rate: f32) -> Self {
+        Self::Sgd { learning_rate }
+    }
+
+    pub fn momentum(learning_rate: f32) -> Self {
+        Self::Momentum {
+            learning_rate,
+            momentum: 0.9,
+        }
+    }
+
+    pub fn adam(learning_rate: f32) -> Self {
+        Self::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+/// Fits a [`Network`] to known input-output pairs with backpropagation,
+/// e.g. to pre-train brains on recorded behaviour before evolving them.
+///
+/// Networks without memory learn from independent samples, see
+/// [`Trainer::train_batch`]. Recurrent layers learn from ordered sequences
+/// with backpropagation through time, see [`Trainer::train_sequences`].
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    loss: Loss,
+    optimizer: Optimizer,
+    /// Running average of the gradients of every trained parameter, used as
+    /// the velocity by momentum
+    first_moments: Vec<f32>,
+    /// Running average of the squared gradients, used by Adam
+    second_moments: Vec<f32>,
+    steps: i32,
+}
+
+impl Trainer {
+    pub fn new(loss: Loss, optimizer: Optimizer) -> Self {
+        Self {
+            loss,
+            optimizer,
+            first_moments: Vec::new(),
+            second_moments: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// Performs one update from the gradients averaged over `samples` and
+    /// returns their average loss before the update.
+    ///
+    /// Fails for networks with recurrent layers, whose outputs depend on
+    /// the samples before; train those with [`Trainer::train_sequences`].
+    pub fn train_batch<'a>(
+        &mut self,
+        network: &mut Network,
+        samples: impl IntoIterator<Item = (&'a [f32], &'a [f32])>,
+    ) -> Result<f32, Error> {
+        if network.layers.iter().any(|layer| layer.kind.is_recurrent()) {
+            return Err(Error::RecurrentNetwork);
+        }
+
+        let mut gradients = Gradients::zeros(network);
+        let mut loss = 0.0;
+        let mut count = 0;
+
+        for (inputs, targets) in samples {
+            loss += gradients.accumulate(network, &[(inputs, targets)], self.loss);
+            count += 1;
+        }
+        if count == 0 {
+            return Err(Error::NoSamples);
+        }
+
+        gradients.scale(1.0 / count as f32);
+        self.apply(network, &gradients);
+
+        Ok(loss / count as f32)
+    }
+
+    /// Goes once over `samples` in mini-batches of `batch_size`, in order,
+    /// and returns the average loss seen along the way; fails like
+    /// [`Trainer::train_batch`].
+    pub fn train_epoch(
+        &mut self,
+        network: &mut Network,
+        samples: &[(Vec<f32>, Vec<f32>)],
+        batch_size: usize,
+    ) -> Result<f32, Error> {
+        assert!(batch_size > 0);
+        if samples.is_empty() {
+            return Err(Error::NoSamples);
+        }
+
+        let mut total = 0.0;
+        for batch in samples.chunks(batch_size) {
+            let loss = self.train_batch(
+                network,
+                batch
+                    .iter()
+                    .map(|(inputs, targets)| (inputs.as_slice(), targets.as_slice())),
+            )?;
+            total += loss * batch.len() as f32;
+        }
+
+        Ok(total / samples.len() as f32)
+    }
+
+    /// Performs one update from the gradients averaged over every step of
+    /// `sequences` and returns their average loss before the update.
+    ///
+    /// Each sequence is propagated in order from an all-zero state, like
+    /// [`Network::propagate_with_state`] with a fresh [`State`](crate::State),
+    /// and its gradients are propagated back through time, so that
+    /// recurrent weights learn too.
+    pub fn train_sequences(
+        &mut self,
+        network: &mut Network,
+        sequences: &[Vec<(Vec<f32>, Vec<f32>)>],
+    ) -> Result<f32, Error> {
+        let mut gradients = Gradients::zeros(network);
+        let mut loss = 0.0;
+        let mut count = 0;
+
+        for sequence in sequences {
+            let sequence: Vec<(&[f32], &[f32])> = sequence
+                .iter()
+                .map(|(inputs, targets)| (inputs.as_slice(), targets.as_slice()))
+                .collect();
+            loss += gradients.accumulate(network, &sequence, self.loss);
+            count += sequence.len();
+        }
+        if count == 0 {
+            return Err(Error::NoSamples);
+        }
+
+        gradients.scale(1.0 / count as f32);
+        self.apply(network, &gradients);
+
+        Ok(loss / count as f32)
+    }
+
+    fn apply(&mut self, network: &mut Network, gradients: &Gradients) {
+        let size = gradients.len();
+        if self.first_moments.is_empty() {
+            self.first_moments = vec![0.0; size];
+            self.second_moments = vec![0.0; size];
+        }
+        assert_eq!(
+            self.first_moments.len(),
+            size,
+            "got networks of different shapes"
+        );
+
+        self.steps += 1;
+
+        let parameters = network
+            .layers
+            .iter_mut()
+            .flat_map(|layer| [&mut layer.weights, &mut layer.recurrent, &mut layer.biases])
+            .flat_map(|values| values.iter_mut());
+        let gradients = gradients
+            .layers
+            .iter()
+            .flat_map(|layer| [&layer.weights, &layer.recurrent, &layer.biases])
+            .flatten();
+
+        let updates = parameters
+            .zip(gradients)
+            .zip(&mut self.first_moments)
+            .zip(&mut self.second_moments);
+
+        for (((parameter, &gradient), first), second) in updates {
+            match self.optimizer {
+                Optimizer::Sgd { learning_rate } => {
+                    *parameter -= learning_rate * gradient;
+                }
+                Optimizer::Momentum {
+                    learning_rate,
+                    momentum,
+                } => {
+                    *first = momentum * *first + gradient;
+                    *parameter -= learning_rate * *first;
+                }
+                Optimizer::Adam {
+                    learning_rate,
+                    beta1,
+                    beta2,
+                    epsilon,
+                } => {
+                    *first = beta1 * *first + (1.0 - beta1) * gradient;
+                    *second = beta2 * *second + (1.0 - beta2) * gradient.powi(2);
+                    let first = *first / (1.0 - beta1.powi(self.steps));
+                    let second = *second / (1.0 - beta2.powi(self.steps));
+                    *parameter -= learning_rate * first / (second.sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+/// Derivatives of the loss with respect to the input weights, recurrent
+/// weights and biases of every layer, laid out like the layers themselves
+#[derive(Debug, Clone)]
+pub(crate) struct Gradients {
+    pub(crate) layers: Vec<LayerGradients>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LayerGradients {
+    pub(crate) weights: Vec<f32>,
+    pub(crate) recurrent: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+}
+
+/// What backpropagation needs to remember of the forward pass of a layer
+/// at one step
+#[derive(Debug, Clone)]
+struct LayerCache {
+    inputs: Vec<f32>,
+    /// Previous outputs of a recurrent layer, all zero on the first step;
+    /// empty for dense layers
+    hidden: Vec<f32>,
+    /// Weighted sum of every row, before the activation
+    sums: Vec<f32>,
+    /// `reset * hidden` of a GRU, empty for other layers
+    reset_hidden: Vec<f32>,
+}
+
+impl Gradients {
+    pub(crate) fn zeros(network: &Network) -> Self {
+        Self {
+            layers: network
+                .layers
+                .iter()
+                .map(|layer| LayerGradients {
+                    weights: vec![0.0; layer.weights.len()],
+                    recurrent: vec![0.0; layer.recurrent.len()],
+                    biases: vec![0.0; layer.biases.len()],
+                })
+                .collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weights.len() + layer.recurrent.len() + layer.biases.len())
+            .sum()
+    }
+
+    fn scale(&mut self, factor: f32) {
+        for layer in &mut self.layers {
+            for value in layer
+                .weights
+                .iter_mut()
+                .chain(&mut layer.recurrent)
+                .chain(&mut layer.biases)
+            {
+                *value *= factor;
+            }
+        }
+    }
+
+    /// Adds the gradients of a sequence of samples, propagated in order
+    /// from an all-zero state, and returns the sum of their losses
+    pub(crate) fn accumulate(
+        &mut self,
+        network: &Network,
+        sequence: &[(&[f32], &[f32])],
+        loss: Loss,
+    ) -> f32 {
+        let empty_state = || -> Vec<Vec<f32>> {
+            network
+                .layers
+                .iter()
+                .map(|layer| {
+                    let size = if layer.kind.is_recurrent() {
+                        layer.output_size
+                    } else {
+                        0
+                    };
+                    vec![0.0; size]
+                })
+                .collect()
+        };
+
+        let mut hidden = empty_state();
+        let mut steps = Vec::with_capacity(sequence.len());
+        let mut total = 0.0;
+
+        for &(inputs, targets) in sequence {
+            let mut caches = Vec::with_capacity(network.layers.len());
+            let outputs = network.layers.iter().zip(&mut hidden).fold(
+                inputs.to_vec(),
+                |inputs, (layer, hidden)| {
+                    let (cache, outputs) = forward(layer, inputs, hidden.clone());
+                    if layer.kind.is_recurrent() {
+                        hidden.clone_from(&outputs);
+                    }
+                    caches.push(cache);
+                    outputs
+                },
+            );
+
+            total += loss.value(&outputs, targets);
+            steps.push((caches, loss.gradient(&outputs, targets)));
+        }
+
+        // derivatives of the loss with respect to the outputs of every
+        // recurrent layer, coming from the step after
+        let mut carried = empty_state();
+        for (caches, mut deltas) in steps.into_iter().rev() {
+            for (((layer, cache), gradients), carried) in network
+                .layers
+                .iter()
+                .zip(&caches)
+                .zip(&mut self.layers)
+                .zip(&mut carried)
+                .rev()
+            {
+                for (delta, carried) in deltas.iter_mut().zip(carried.iter()) {
+                    *delta += carried;
+                }
+                let (input_deltas, hidden_deltas) = backward(layer, cache, &deltas, gradients);
+                *carried = hidden_deltas;
+                deltas = input_deltas;
+            }
+        }
+
+        total
+    }
+}
+
+/// Row `row` of a row-major matrix with `columns` columns
+fn row(matrix: &[f32], columns: usize, row: usize) -> &[f32] {
+    &matrix[row * columns..][..columns]
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Forward pass of one step that keeps what backpropagation needs around,
+/// `hidden` being the previous outputs of a recurrent layer
+fn forward(layer: &Layer, inputs: Vec<f32>, hidden: Vec<f32>) -> (LayerCache, Vec<f32>) {
+    assert_eq!(inputs.len(), layer.input_size);
+
+    let size = layer.output_size;
+    let mut sums: Vec<f32> = layer
+        .weights
+        .chunks_exact(layer.input_size)
+        .zip(&layer.biases)
+        .map(|(row, bias)| bias + dot(row, &inputs))
+        .collect();
+    let mut reset_hidden = Vec::new();
+
+    let outputs = match layer.kind {
+        LayerKind::Dense => sums
+            .iter()
+            .map(|&sum| layer.activation.apply(sum))
+            .collect(),
+        LayerKind::Elman => {
+            for (i, sum) in sums.iter_mut().enumerate() {
+                *sum += dot(row(&layer.recurrent, size, i), &hidden);
+            }
+            sums.iter()
+                .map(|&sum| layer.activation.apply(sum))
+                .collect()
+        }
+        LayerKind::Gru => {
+            for (i, sum) in sums[..2 * size].iter_mut().enumerate() {
+                *sum += dot(row(&layer.recurrent, size, i), &hidden);
+            }
+            reset_hidden = (0..size)
+                .map(|i| Activation::Sigmoid.apply(sums[size + i]) * hidden[i])
+                .collect();
+            for i in 0..size {
+                sums[2 * size + i] += dot(row(&layer.recurrent, size, 2 * size + i), &reset_hidden);
+            }
+
+            (0..size)
+                .map(|i| {
+                    let update = Activation::Sigmoid.apply(sums[i]);
+                    let candidate = layer.activation.apply(sums[2 * size + i]);
+                    (1.0 - update) * hidden[i] + update * candidate
+                })
+                .collect()
+        }
+    };
+
+    let cache = LayerCache {
+        inputs,
+        hidden,
+        sums,
+        reset_hidden,
+    };
+    (cache, outputs)
+}
+
+/// Accumulates the gradients of a layer given the derivatives of the loss
+/// with respect to its outputs, and returns those with respect to its
+/// inputs and to its previous outputs.
+fn backward(
+    layer: &Layer,
+    cache: &LayerCache,
+    deltas: &[f32],
+    gradients: &mut LayerGradients,
+) -> (Vec<f32>, Vec<f32>) {
+    let size = layer.output_size;
+    let sums = &cache.sums;
+    let hidden = &cache.hidden;
+    let mut hidden_deltas = vec![0.0; hidden.len()];
+
+    // derivative of the loss with respect to the weighted sum of every row
+    let rows: Vec<f32> = match layer.kind {
+        LayerKind::Dense | LayerKind::Elman => deltas
+            .iter()
+            .zip(sums)
+            .map(|(delta, &sum)| delta * layer.activation.derivative(sum))
+            .collect(),
+        LayerKind::Gru => {
+            let mut rows = vec![0.0; 3 * size];
+            for (i, &delta) in deltas.iter().enumerate() {
+                let (update, candidate) = (sums[i], sums[2 * size + i]);
+                let z = Activation::Sigmoid.apply(update);
+
+                rows[i] = delta
+                    * (layer.activation.apply(candidate) - hidden[i])
+                    * Activation::Sigmoid.derivative(update);
+                rows[2 * size + i] = delta * z * layer.activation.derivative(candidate);
+                hidden_deltas[i] += delta * (1.0 - z);
+            }
+
+            // the candidate sees the previous outputs through the reset gate
+            let mut reset_hidden_deltas = vec![0.0; size];
+            for i in 0..size {
+                let delta = rows[2 * size + i];
+                let weights = row(&layer.recurrent, size, 2 * size + i);
+                let gradients = &mut gradients.recurrent[(2 * size + i) * size..][..size];
+                for j in 0..size {
+                    gradients[j] += delta * cache.reset_hidden[j];
+                    reset_hidden_deltas[j] += delta * weights[j];
+                }
+            }
+            for j in 0..size {
+                let reset = sums[size + j];
+                rows[size + j] =
+                    reset_hidden_deltas[j] * hidden[j] * Activation::Sigmoid.derivative(reset);
+                hidden_deltas[j] += reset_hidden_deltas[j] * Activation::Sigmoid.apply(reset);
+            }
+            rows
+        }
+    };
+
+    // rows applied to the previous outputs as they are: all of an Elman
+    // layer, the update and reset gates of a GRU
+    let direct_rows = match layer.kind {
+        LayerKind::Dense => 0,
+        LayerKind::Elman => size,
+        LayerKind::Gru => 2 * size,
+    };
+    for (i, &delta) in rows[..direct_rows].iter().enumerate() {
+        let weights = row(&layer.recurrent, size, i);
+        let gradients = &mut gradients.recurrent[i * size..][..size];
+        for j in 0..size {
+            gradients[j] += delta * hidden[j];
+            hidden_deltas[j] += delta * weights[j];
+        }
+    }
+
+    let mut input_deltas = vec![0.0; layer.input_size];
+
+    for ((row, weights), (&delta, bias)) in layer
+        .weights
+        .chunks_exact(layer.input_size)
+        .zip(gradients.weights.chunks_exact_mut(layer.input_size))
+        .zip(rows.iter().zip(&mut gradients.biases))
+    {
+        *bias += delta;
+        for (((weight, gradient), input), input_delta) in row
+            .iter()
+            .zip(weights)
+            .zip(&cache.inputs)
+            .zip(&mut input_deltas)
+        {
+            *gradient += delta * input;
+            *input_delta += delta * weight;
+        }
+    }
+
+    (input_deltas, hidden_deltas)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::{Head, HeadOutput, LayerTopology, OutputHeads, argmax};
+
+    fn network(rng: &mut ChaCha8Rng) -> Network {
+        let topology = [
+            (3, Activation::Identity, LayerKind::Dense),
+            (4, Activation::Tanh, LayerKind::Elman),
+            (3, Activation::Softsign, LayerKind::Gru),
+            (2, Activation::Sigmoid, LayerKind::Dense),
+        ]
+        .map(|(neurons, activation, kind)| LayerTopology {
+            neurons,
+            activation,
+            kind,
+        });
+
+        Network::random(rng, &topology)
+    }
+
+    fn xor() -> Vec<(Vec<f32>, Vec<f32>)> {
+        [
+            (0.0, 0.0, 0.0),
+            (0.0, 1.0, 1.0),
+            (1.0, 0.0, 1.0),
+            (1.0, 1.0, 0.0),
+        ]
+        .into_iter()
+        .map(|(a, b, y)| (vec![a, b], vec![y]))
+        .collect()
+    }
+
+    fn xor_network() -> Network {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let topology = [
+            (2, Activation::Identity),
+            (4, Activation::Tanh),
+            (1, Activation::Sigmoid),
+        ]
+        .map(|(neurons, activation)| LayerTopology {
+            neurons,
+            activation,
+            kind: LayerKind::Dense,
+        });
+
+        Network::random(&mut rng, &topology)
+    }
+
+    fn sequence() -> Vec<(Vec<f32>, Vec<f32>)> {
+        vec![
+            (vec![0.3, -0.8, 0.5], vec![0.2, 0.9]),
+            (vec![-0.4, 0.1, 0.9], vec![0.7, 0.4]),
+            (vec![0.6, 0.2, -0.5], vec![0.1, 0.5]),
+        ]
+    }
+
+    #[test]
+    fn forward_matches_propagate_with_state() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = network(&mut rng);
+        let mut state = network.state();
+        let mut scratch = Scratch::for_network(&network);
+        let mut hidden: Vec<Vec<f32>> = network
+            .layers
+            .iter()
+            .map(|layer| match layer.kind {
+                LayerKind::Dense => Vec::new(),
+                _ => vec![0.0; layer.output_size],
+            })
+            .collect();
+
+        for (inputs, _) in sequence() {
+            let outputs = network.layers.iter().zip(&mut hidden).fold(
+                inputs.clone(),
+                |inputs, (layer, hidden)| {
+                    let (_, outputs) = forward(layer, inputs, hidden.clone());
+                    if layer.kind.is_recurrent() {
+                        hidden.clone_from(&outputs);
+                    }
+                    outputs
+                },
+            );
+
+            assert_relative_eq!(
+                outputs.as_slice(),
+                network.propagate_with_state(&inputs, &mut state, &mut scratch),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn gradients_match_finite_differences() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = network(&mut rng);
+        let sequences = [sequence()];
+        let steps: Vec<(&[f32], &[f32])> = sequences[0]
+            .iter()
+            .map(|(inputs, targets)| (inputs.as_slice(), targets.as_slice()))
+            .collect();
+
+        for loss in [
+            Loss::MeanSquaredError,
+            Loss::CrossEntropy,
+            Loss::SoftmaxCrossEntropy,
+        ] {
+            let mut gradients = Gradients::zeros(&network);
+            gradients.accumulate(&network, &steps, loss);
+
+            let loss_with = |layer: usize, parameters: usize, index: usize, delta: f32| {
+                let mut network = network.clone();
+                let layer = &mut network.layers[layer];
+                let values = match parameters {
+                    0 => &mut layer.weights,
+                    1 => &mut layer.recurrent,
+                    _ => &mut layer.biases,
+                };
+                values[index] += delta;
+                loss.evaluate_sequences(&network, &sequences).unwrap() * steps.len() as f32
+            };
+
+            let h = 1e-2;
+            for (index, layer) in gradients.layers.iter().enumerate() {
+                for (parameters, values) in [&layer.weights, &layer.recurrent, &layer.biases]
+                    .into_iter()
+                    .enumerate()
+                {
+                    for (i, &gradient) in values.iter().enumerate() {
+                        let numeric = (loss_with(index, parameters, i, h)
+                            - loss_with(index, parameters, i, -h))
+                            / (2.0 * h);
+                        assert_relative_eq!(gradient, numeric, epsilon = 2e-3);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_cannot_train_recurrent_networks() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = network(&mut rng);
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::sgd(0.1));
+
+        assert_eq!(
+            trainer.train_epoch(&mut network, &sequence(), 2),
+            Err(Error::RecurrentNetwork)
+        );
+    }
+
+    #[test]
+    fn no_samples_is_an_error() {
+        let mut network = xor_network();
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::sgd(0.1));
+
+        assert_eq!(
+            trainer.train_epoch(&mut network, &[], 4),
+            Err(Error::NoSamples)
+        );
+        assert_eq!(trainer.train_batch(&mut network, []), Err(Error::NoSamples));
+        assert_eq!(
+            trainer.train_sequences(&mut network, &[vec![]]),
+            Err(Error::NoSamples)
+        );
+        assert_eq!(
+            Loss::MeanSquaredError.evaluate(&network, &[]),
+            Err(Error::NoSamples)
+        );
+        assert_eq!(
+            Loss::MeanSquaredError.evaluate_sequences(&network, &[vec![]]),
+            Err(Error::NoSamples)
+        );
+    }
+
+    #[test]
+    fn elman_learns_to_delay() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let topology = [
+            (1, Activation::Identity, LayerKind::Dense),
+            (6, Activation::Tanh, LayerKind::Elman),
+            (1, Activation::Identity, LayerKind::Dense),
+        ]
+        .map(|(neurons, activation, kind)| LayerTopology {
+            neurons,
+            activation,
+            kind,
+        });
+        let mut network = Network::random(&mut rng, &topology);
+
+        // each output is the input one step before
+        let inputs = [0.5, -0.3, 0.8, -0.6, 0.1, 0.4, -0.9, 0.2];
+        let sequences = vec![
+            inputs
+                .iter()
+                .zip([0.0].iter().chain(&inputs))
+                .map(|(&input, &target)| (vec![input], vec![target]))
+                .collect::<Vec<_>>(),
+        ];
+
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::adam(0.02));
+        let before = Loss::MeanSquaredError
+            .evaluate_sequences(&network, &sequences)
+            .unwrap();
+        for _ in 0..500 {
+            trainer.train_sequences(&mut network, &sequences).unwrap();
+        }
+
+        assert!(
+            Loss::MeanSquaredError
+                .evaluate_sequences(&network, &sequences)
+                .unwrap()
+                < before / 10.0
+        );
+    }
+
+    #[test]
+    fn optimizers_reduce_loss() {
+        let samples = xor();
+
+        for optimizer in [
+            Optimizer::sgd(0.5),
+            Optimizer::momentum(0.1),
+            Optimizer::adam(0.05),
+        ] {
+            let mut network = xor_network();
+            let mut trainer = Trainer::new(Loss::MeanSquaredError, optimizer);
+            let before = Loss::MeanSquaredError.evaluate(&network, &samples).unwrap();
+
+            for _ in 0..100 {
+                trainer.train_epoch(&mut network, &samples, 4).unwrap();
+            }
+
+            assert!(Loss::MeanSquaredError.evaluate(&network, &samples).unwrap() < before);
+        }
+    }
+
+    #[test]
+    fn adam_learns_xor() {
+        let samples = xor();
+        let mut network = xor_network();
+        let mut trainer = Trainer::new(Loss::CrossEntropy, Optimizer::adam(0.05));
+
+        for _ in 0..1000 {
+            trainer.train_epoch(&mut network, &samples, 4).unwrap();
+        }
+
+        for (inputs, targets) in &samples {
+            assert_relative_eq!(
+                network.propagate(inputs.clone())[0],
+                targets[0],
+                epsilon = 0.1
+            );
+        }
+    }
+
+    #[test]
+    fn softmax_cross_entropy_learns_discrete_choices() {
+        let heads = OutputHeads::new(vec![Head::Discrete { choices: 2 }]);
+        let samples: Vec<_> = xor()
+            .into_iter()
+            .map(|(inputs, targets)| (inputs, vec![1.0 - targets[0], targets[0]]))
+            .collect();
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let topology = [
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Identity,
+                kind: LayerKind::Dense,
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+            },
+            heads.layer(),
+        ];
+        let mut network = Network::random(&mut rng, &topology);
+        let mut trainer = Trainer::new(Loss::SoftmaxCrossEntropy, Optimizer::adam(0.05));
+
+        for _ in 0..1000 {
+            trainer.train_epoch(&mut network, &samples, 4).unwrap();
+        }
+
+        for (inputs, targets) in &samples {
+            let outputs = network.propagate(inputs.clone());
+            let [HeadOutput::Discrete { choice, .. }] = heads.apply(&outputs)[..] else {
+                panic!("expected a single discrete head");
+            };
+            assert_eq!(choice, argmax(targets));
+        }
+    }
+}
@@ -2,7 +2,11 @@ mod animal;
 mod food;
 mod world;
 
-use std::time::Duration;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    time::Duration,
+};
 
 pub use animal::*;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
@@ -16,7 +20,7 @@ use ratatui::{
     text::Line,
     widgets::{Block, Paragraph, Widget, canvas::Canvas},
 };
-use simulation::{self as sim, Stats};
+use simulation::{self as sim, Stats, ga::history::History};
 pub use world::*;
 
 type Result = std::io::Result<()>;
@@ -31,6 +35,9 @@ pub struct App {
     tick_rate: f32,
     age: usize,
     stats: Stats,
+    history: History,
+    /// Outcome of the last action worth reporting, e.g. saving the history
+    status: Option<String>,
     exit: bool,
 }
 
@@ -43,11 +50,9 @@ impl App {
             rng,
             tick_rate,
             age: 0,
-            stats: Stats {
-                avg_fitness: 0.0,
-                min_fitness: 0.0,
-                max_fitness: 0.0,
-            },
+            stats: Stats::default(),
+            history: History::new(),
+            status: None,
             exit: false,
         }
     }
@@ -62,6 +67,7 @@ impl App {
             self.handle_events()?;
             if let Some(stats) = self.sim.step(&mut self.rng) {
                 self.age += 1;
+                self.history.push(stats.clone());
                 self.stats = stats;
             }
         }
@@ -82,18 +88,23 @@ impl App {
 
     fn handle_events(&mut self) -> Result {
         let timeout = Duration::from_secs_f32(1.0 / self.tick_rate);
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Up => self.faster(),
-                        KeyCode::Down => self.slower(),
-                        KeyCode::Right => self.train_generation(),
-                        KeyCode::Char('r') => self.restart(),
-                        KeyCode::Char('q') | KeyCode::Esc => self.exit(),
-                        _ => {}
-                    }
+        if event::poll(timeout)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Up => self.faster(),
+                KeyCode::Down => self.slower(),
+                KeyCode::Right => self.train_generation(),
+                KeyCode::Char('r') => self.restart(),
+                KeyCode::Char('s') => {
+                    self.status = Some(match self.save_history() {
+                        Ok(()) => "History saved to history.csv and history.jsonl".to_string(),
+                        Err(err) => format!("Could not save history: {err}"),
+                    });
                 }
+                KeyCode::Char('q') | KeyCode::Esc => self.exit(),
+                _ => {}
             }
         }
         Ok(())
@@ -118,12 +129,25 @@ impl App {
     fn render_stats(&self) -> impl Widget + '_ {
         let block = Block::bordered().title("Stats");
         Paragraph::new(format!(
-            "Generation: {}, Day: {}, avg: {}, min: {}, max: {}",
+            "Generation: {}, Day: {}, avg: {}, median: {}, std: {:.2}, min: {}, max: {}, diversity: {:.3}, best ever: {}",
             self.age,
             self.sim.age,
             self.stats.avg_fitness,
+            self.stats.median_fitness,
+            self.stats.std_fitness,
             self.stats.min_fitness,
-            self.stats.max_fitness
+            self.stats.max_fitness,
+            self.stats
+                .diversity
+                .as_ref()
+                .map_or(0.0, |diversity| diversity.mean_distance),
+            self.sim
+                .hall_of_fame()
+                .best()
+                .map_or("-".to_string(), |best| format!(
+                    "{} (generation {})",
+                    best.fitness, best.generation
+                ))
         ))
         .block(block)
     }
@@ -138,8 +162,13 @@ impl App {
             "<↑> ".blue().bold(),
             " Slower ".into(),
             "<↓> ".blue().bold(),
+            " Save history ".into(),
+            "<s> ".blue().bold(),
         ]);
-        let block = Block::bordered().title("Controls");
+        let mut block = Block::bordered().title("Controls");
+        if let Some(status) = &self.status {
+            block = block.title_bottom(status.as_str());
+        }
         Paragraph::new(instructions.centered()).block(block)
     }
 
@@ -149,6 +178,19 @@ impl App {
 
     fn restart(&mut self) {
         self.sim = sim::Simulation::random(&mut self.rng);
+        self.history = History::new();
+    }
+
+    /// Writes the stats of every generation so far to `history.csv` and
+    /// `history.jsonl` in the working directory
+    fn save_history(&self) -> Result {
+        let mut csv = BufWriter::new(File::create("history.csv")?);
+        self.history.write_csv(&mut csv)?;
+        csv.flush()?;
+
+        let mut jsonl = BufWriter::new(File::create("history.jsonl")?);
+        self.history.write_jsonl(&mut jsonl)?;
+        jsonl.flush()
     }
 
     fn faster(&mut self) {
@@ -161,6 +203,7 @@ impl App {
 
     fn train_generation(&mut self) {
         let stats = self.sim.train(&mut self.rng);
+        self.history.push(stats.clone());
         self.stats = stats;
         self.age += 1;
     }
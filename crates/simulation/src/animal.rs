@@ -1,8 +1,12 @@
 use std::f32::consts::FRAC_PI_2;
 
-use crate::{brain::Brain, eye::Eye};
+use crate::{
+    brain::{Brain, BrainKind},
+    eye::Eye,
+};
 use genetic_algorithm::Genotype;
 use nalgebra as na;
+use neural_network as nn;
 use rand::{Rng, RngCore};
 
 pub(crate) const SPEED_MIN: f32 = 0.001;
@@ -22,7 +26,8 @@ pub struct Animal {
 }
 
 impl Animal {
-    pub fn new(eye: Eye, brain: Brain, rng: &mut dyn RngCore) -> Self {
+    pub fn new(eye: Eye, mut brain: Brain, rng: &mut dyn RngCore) -> Self {
+        brain.reset();
         Self {
             position: rng.random(),
             rotation: rng.random(),
@@ -33,17 +38,25 @@ impl Animal {
             eaten: 0,
         }
     }
-    pub fn random(rng: &mut dyn RngCore) -> Self {
+    pub fn random(rng: &mut dyn RngCore, brain: BrainKind) -> Self {
         let eye = Eye::default();
-        let brain = Brain::random(rng, &eye);
+        let brain = Brain::random(rng, &eye, brain);
         Self::new(eye, brain, rng)
     }
 
-    pub(crate) fn from_genotype(genotype: Genotype, rng: &mut dyn RngCore) -> Self {
+    pub(crate) fn from_genotype(
+        genotype: Genotype,
+        prototype: &Brain,
+        rng: &mut dyn RngCore,
+    ) -> Result<Self, nn::Error> {
         let eye = Eye::default();
-        let brain = Brain::from_genotype(genotype, &eye);
+        let brain = Brain::from_genotype(genotype, prototype)?;
 
-        Self::new(eye, brain, rng)
+        Ok(Self::new(eye, brain, rng))
+    }
+
+    pub(crate) fn from_neat(genome: nn::NeatGenome, rng: &mut dyn RngCore) -> Self {
+        Self::new(Eye::default(), Brain::from_neat(genome), rng)
     }
 
     pub fn position(&self) -> na::Point2<f32> {
@@ -56,4 +69,15 @@ impl Animal {
     pub fn size(&self) -> f32 {
         self.size
     }
+
+    /// Food eaten in the current generation
+    pub fn eaten(&self) -> usize {
+        self.eaten
+    }
+
+    /// Layered network driving this animal, if its brain is one, e.g. to
+    /// render it with [`nn::Network::to_svg`]
+    pub fn network(&self) -> Option<&nn::Network> {
+        self.brain.controller.network()
+    }
 }
@@ -1,8 +1,11 @@
-use crate::*;
+use crate::{brain::Brain, *};
 
+#[derive(Debug, Clone)]
 pub struct AnimalIndividual {
     fitness: f32,
     genotype: ga::Genotype,
+    /// Genome of NEAT brains, which is bred instead of `genotype`
+    neat: Option<nn::NeatGenome>,
 }
 
 impl ga::Individual for AnimalIndividual {
@@ -10,6 +13,7 @@ impl ga::Individual for AnimalIndividual {
         Self {
             fitness: 0.0,
             genotype,
+            neat: None,
         }
     }
 
@@ -27,10 +31,56 @@ impl AnimalIndividual {
         Self {
             fitness: animal.eaten as f32,
             genotype: animal.brain.as_genotype(),
+            neat: animal.brain.neat().cloned(),
         }
     }
 
-    pub fn into_animal(self, rng: &mut dyn RngCore) -> Animal {
-        Animal::from_genotype(self.genotype, rng)
+    pub fn is_neat(&self) -> bool {
+        self.neat.is_some()
+    }
+
+    pub fn neat(&self) -> Option<&nn::NeatGenome> {
+        self.neat.as_ref()
+    }
+
+    /// Child of two NEAT parents: structure from the fitter one, then
+    /// mutated in weights and topology
+    pub fn breed_neat(
+        rng: &mut dyn RngCore,
+        parent_a: &Self,
+        parent_b: &Self,
+        mutation: &nn::NeatMutation,
+        innovations: &mut nn::Innovations,
+    ) -> Self {
+        let (fitter, other) = if parent_a.fitness >= parent_b.fitness {
+            (parent_a, parent_b)
+        } else {
+            (parent_b, parent_a)
+        };
+        let (Some(fitter), Some(other)) = (&fitter.neat, &other.neat) else {
+            panic!("got parents without NEAT genomes");
+        };
+
+        let mut child = nn::NeatGenome::crossover(rng, fitter, other);
+        mutation.mutate(rng, &mut child, innovations);
+
+        Self {
+            fitness: 0.0,
+            genotype: child.weights().collect(),
+            neat: Some(child),
+        }
+    }
+
+    /// `prototype` decides the kind and shape of the new animal's brain,
+    /// fails when the genotype was evolved for another shape
+    pub fn into_animal(
+        self,
+        prototype: &Brain,
+        rng: &mut dyn RngCore,
+    ) -> Result<Animal, nn::Error> {
+        match self.neat {
+            Some(genome) => Ok(Animal::from_neat(genome, rng)),
+            None => Animal::from_genotype(self.genotype, prototype, rng),
+        }
     }
 }
@@ -1,40 +1,188 @@
+use std::f32::consts::FRAC_PI_8;
+
 use crate::*;
 
+/// Speed and rotation
+const OUTPUTS: usize = 2;
+
+/// Builds a controller with random parameters for the given number of
+/// inputs and outputs
+pub type ControllerFactory =
+    fn(rng: &mut dyn RngCore, inputs: usize, outputs: usize) -> Box<dyn nn::Controller>;
+
+/// Which kind of controller drives the animals
+#[derive(Debug, Clone, Copy, Default)]
+pub enum BrainKind {
+    /// Layered network with a recurrent hidden layer
+    #[default]
+    Network,
+    /// Continuous-time recurrent network
+    Ctrnn,
+    /// NEAT network starting with the inputs wired straight to the outputs
+    /// and growing hidden nodes and connections over the generations
+    Neat,
+    /// Any other controller, e.g.
+    /// `BrainKind::Custom(|_, inputs, _| Box::new(SeekFood::new(inputs)))`
+    Custom(ControllerFactory),
+}
+
+impl BrainKind {
+    fn build(self, rng: &mut dyn RngCore, inputs: usize) -> Box<dyn nn::Controller> {
+        match self {
+            Self::Network => Box::new(nn::NetworkController::new(nn::Network::random(
+                rng,
+                &Brain::topology(inputs),
+            ))),
+            Self::Ctrnn => Box::new(nn::CtrnnController::new(nn::Ctrnn::random(
+                rng,
+                &Brain::ctrnn_topology(inputs),
+            ))),
+            Self::Neat => Box::new(nn::NeatController::new(nn::NeatNetwork::new(
+                nn::NeatGenome::minimal(rng, inputs, OUTPUTS, nn::Activation::Tanh),
+            ))),
+            Self::Custom(factory) => factory(rng, inputs, OUTPUTS),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Brain {
-    pub(crate) nn: nn::Network,
+    pub(crate) controller: Box<dyn nn::Controller>,
 }
 
 impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Self {
-        Self {
-            nn: nn::Network::random(rng, &Self::topology(eye)),
-        }
+    pub fn random(rng: &mut dyn RngCore, eye: &Eye, kind: BrainKind) -> Self {
+        let controller = kind.build(rng, eye.cells());
+        assert_eq!(controller.inputs(), eye.cells());
+        assert_eq!(controller.outputs(), OUTPUTS);
+
+        Self { controller }
+    }
+
+    /// Brain shaped like `prototype`, with its parameters taken from
+    /// `genotype`
+    pub(crate) fn from_genotype(
+        genotype: ga::Genotype,
+        prototype: &Brain,
+    ) -> Result<Self, nn::Error> {
+        let genes: Vec<f32> = genotype.into_iter().collect();
+        Ok(Self {
+            controller: prototype.controller.with_genes(&genes)?,
+        })
     }
 
-    pub(crate) fn from_genotype(genotype: ga::Genotype, eye: &Eye) -> Self {
+    pub(crate) fn from_neat(genome: nn::NeatGenome) -> Self {
         Self {
-            nn: nn::Network::from_weights(&Self::topology(eye), genotype),
+            controller: Box::new(nn::NeatController::new(nn::NeatNetwork::new(genome))),
         }
     }
 
+    pub(crate) fn neat(&self) -> Option<&nn::NeatGenome> {
+        self.controller.neat()
+    }
+
     pub(crate) fn as_genotype(&self) -> ga::Genotype {
-        self.nn.weights().collect()
+        self.controller.to_genes().into_iter().collect()
     }
 
-    fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
+    pub(crate) fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        self.controller.propagate(inputs)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.controller.reset();
+    }
+
+    fn topology(inputs: usize) -> [nn::LayerTopology; 3] {
         [
             nn::LayerTopology {
-                neurons: eye.cells(),
+                neurons: inputs,
+                activation: nn::Activation::Identity,
+                kind: nn::LayerKind::Dense,
+            },
+            nn::LayerTopology {
+                neurons: 2 * inputs,
+                activation: nn::Activation::Relu,
+                // remembers food that has just left the field of view
+                kind: nn::LayerKind::Elman,
             },
+            // tanh keeps the speed and rotation deltas symmetric around zero
             nn::LayerTopology {
-                neurons: 2 * eye.cells(),
+                neurons: OUTPUTS,
+                activation: nn::Activation::Tanh,
+                kind: nn::LayerKind::Dense,
             },
-            nn::LayerTopology { neurons: 2 },
         ]
     }
 
-    pub(crate) fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.nn.propagate(inputs)
+    fn ctrnn_topology(inputs: usize) -> nn::CtrnnTopology {
+        nn::CtrnnTopology {
+            inputs,
+            neurons: inputs + OUTPUTS,
+            outputs: OUTPUTS,
+            activation: nn::Activation::Tanh,
+            step: 0.1,
+        }
+    }
+}
+
+/// Hand-written baseline: speeds up and turns towards the eye cell seeing
+/// the most food, wanders in circles when nothing is in sight.
+#[derive(Debug, Clone)]
+pub struct SeekFood {
+    inputs: usize,
+    outputs: [f32; OUTPUTS],
+}
+
+impl SeekFood {
+    pub fn new(inputs: usize) -> Self {
+        assert!(inputs > 0);
+
+        Self {
+            inputs,
+            outputs: [0.0; OUTPUTS],
+        }
+    }
+}
+
+impl nn::Controller for SeekFood {
+    fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    fn outputs(&self) -> usize {
+        OUTPUTS
+    }
+
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        let (best, energy) = inputs
+            .iter()
+            .enumerate()
+            .fold((0, 0.0), |best, (cell, &energy)| {
+                if energy > best.1 {
+                    (cell, energy)
+                } else {
+                    best
+                }
+            });
+
+        self.outputs = if energy > 0.0 {
+            // cells go from the right edge (-1) to the left edge (1) of the view
+            let center = (self.inputs - 1) as f32 / 2.0;
+            let offset = (best as f32 - center) / center.max(1.0);
+            [SPEED_ACCEL, offset * FRAC_PI_8]
+        } else {
+            [-SPEED_ACCEL, FRAC_PI_8]
+        };
+
+        &self.outputs
+    }
+
+    fn to_genes(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    fn with_genes(&self, _genes: &[f32]) -> Result<Box<dyn nn::Controller>, nn::Error> {
+        Ok(Box::new(Self::new(self.inputs)))
     }
 }
@@ -6,42 +6,81 @@ mod food;
 mod world;
 
 pub use animal::*;
-use animal_individual::*;
+pub use animal_individual::*;
+pub use brain::{BrainKind, ControllerFactory, SeekFood};
 use eye::*;
 pub use food::*;
 pub use genetic_algorithm::{
-    self as ga, crossover_method::UniformCrossover, mutation_method::GaussianMutation,
-    selection_method::RouletteWheelSelection, stats::Stats,
+    self as ga, crossover_method::UniformCrossover, hall_of_fame::HallOfFame, history::History,
+    mutation_method::GaussianMutation, selection_method::RouletteWheelSelection, stats::Stats,
+    termination::Criterion,
 };
 use nalgebra::{Rotation2, wrap};
 use neural_network as nn;
 use rand::{Rng, RngCore};
+use std::mem;
 pub use world::*;
 
 const GENERATION_LENGTH: usize = 2500;
+const HALL_OF_FAME_SIZE: usize = 10;
 
 #[derive(Debug)]
 pub struct Simulation {
     world: World,
     ga: ga::GeneticAlgorithm<RouletteWheelSelection, UniformCrossover, GaussianMutation>,
+    /// Brains of all animals in the same order as `world.animals` and their
+    /// memory, fresh whenever animals are created; `None` unless all brains
+    /// are backed by layered networks
+    batch: Option<(nn::NetworkBatch, nn::State)>,
+    /// Innovation numbers shared by all NEAT brains, set up on their first
+    /// evolution
+    innovations: Option<nn::Innovations>,
+    scratch: nn::Scratch,
+    vision: Vec<f32>,
+    hall_of_fame: HallOfFame<AnimalIndividual>,
     pub age: usize,
 }
 
 impl Simulation {
     pub fn random(rng: &mut dyn RngCore) -> Self {
-        let world = World::random(rng);
+        Self::with_brain(rng, BrainKind::default())
+    }
+
+    pub fn with_brain(rng: &mut dyn RngCore, brain: BrainKind) -> Self {
+        let world = World::random(rng, brain);
         let ga = ga::GeneticAlgorithm::new(
             RouletteWheelSelection,
             UniformCrossover,
             GaussianMutation::new(0.01, 0.3),
-        );
-        Self { world, ga, age: 0 }
+        )
+        .with_diversity(true);
+        let batch = Self::batch(&world);
+        Self {
+            world,
+            ga,
+            batch,
+            innovations: None,
+            scratch: nn::Scratch::new(),
+            vision: Vec::new(),
+            hall_of_fame: HallOfFame::new(HALL_OF_FAME_SIZE),
+            age: 0,
+        }
     }
 
     pub fn world(&self) -> &World {
         &self.world
     }
 
+    /// Number of generations evolved so far
+    pub fn generation(&self) -> usize {
+        self.ga.generation()
+    }
+
+    /// Fittest animals of all generations so far
+    pub fn hall_of_fame(&self) -> &HallOfFame<AnimalIndividual> {
+        &self.hall_of_fame
+    }
+
     pub fn step(&mut self, rng: &mut dyn RngCore) -> Option<Stats> {
         self.handle_collision(rng);
         self.process_brains();
@@ -62,6 +101,16 @@ impl Simulation {
             }
         }
     }
+
+    /// Trains generation after generation until any of `criteria` is met,
+    /// returning that criterion and the stats of the generations trained
+    pub fn train_until(
+        &mut self,
+        rng: &mut dyn RngCore,
+        criteria: &[Criterion],
+    ) -> (Criterion, History) {
+        ga::termination::record_until(criteria, || self.train(rng))
+    }
     fn evolve(&mut self, rng: &mut dyn RngCore) -> Stats {
         self.age = 0;
         let current_population: Vec<_> = self
@@ -70,34 +119,97 @@ impl Simulation {
             .iter()
             .map(AnimalIndividual::from_animal)
             .collect();
+        let generation = self.ga.generation();
+        self.hall_of_fame.record(generation, &current_population);
 
-        let (evolved_population, stats) = self.ga.evolve(rng, &current_population);
+        let (evolved_population, stats) = if current_population
+            .iter()
+            .all(AnimalIndividual::is_neat)
+        {
+            let innovations = self.innovations.get_or_insert_with(|| {
+                nn::Innovations::new(current_population.iter().filter_map(AnimalIndividual::neat))
+            });
+            let mutation = nn::NeatMutation::default();
+            self.ga
+                .evolve_with(rng, &current_population, |rng, parent_a, parent_b| {
+                    AnimalIndividual::breed_neat(rng, parent_a, parent_b, &mutation, innovations)
+                })
+        } else {
+            self.ga.evolve(rng, &current_population)
+        };
 
-        self.world.animals = evolved_population
+        let previous = mem::take(&mut self.world.animals);
+        let offspring: Vec<_> = evolved_population
             .into_iter()
-            .map(|individual| individual.into_animal(rng))
+            .map(|individual| individual.into_animal(&previous[0].brain, rng))
             .collect();
+        self.world.animals = offspring
+            .into_iter()
+            .zip(previous)
+            // offspring whose genes do not fit the brain are replaced by
+            // the animal of the previous generation, reborn
+            .map(|(offspring, previous)| {
+                offspring.unwrap_or_else(|_| Animal::new(previous.eye, previous.brain, rng))
+            })
+            .collect();
+        self.batch = Self::batch(&self.world);
 
         for food in &mut self.world.foods {
             food.position = rng.random();
         }
+
         stats
     }
+    fn batch(world: &World) -> Option<(nn::NetworkBatch, nn::State)> {
+        let networks: Option<Vec<_>> = world
+            .animals
+            .iter()
+            .map(|animal| animal.brain.controller.network())
+            .collect();
+
+        let batch = nn::NetworkBatch::new(networks?);
+        let state = batch.state();
+        Some((batch, state))
+    }
+
     pub fn process_brains(&mut self) {
-        for animal in &mut self.world.animals {
-            let vision =
-                animal
-                    .eye
-                    .process_vision(animal.position(), animal.rotation(), &self.world.foods);
-            let output = animal.brain.propagate(vision);
-            let speed = output[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
-            let rotation = output[1].clamp(-ROT_ACCEL, ROT_ACCEL);
-
-            animal.speed = (animal.speed + speed).clamp(-SPEED_MIN, SPEED_MAX);
-            animal.rotation = Rotation2::new(animal.rotation.angle() + rotation);
+        let Some((batch, memory)) = &mut self.batch else {
+            for animal in &mut self.world.animals {
+                let vision = animal.eye.process_vision(
+                    animal.position(),
+                    animal.rotation(),
+                    &self.world.foods,
+                );
+                let output = animal.brain.propagate(&vision);
+                let output = [output[0], output[1]];
+                Self::steer(animal, &output);
+            }
+            return;
+        };
+
+        self.vision.clear();
+        for animal in &self.world.animals {
+            self.vision.extend(animal.eye.process_vision(
+                animal.position(),
+                animal.rotation(),
+                &self.world.foods,
+            ));
+        }
+
+        let outputs = batch.propagate_with_state(&self.vision, memory, &mut self.scratch);
+        for (animal, output) in self.world.animals.iter_mut().zip(outputs.chunks_exact(2)) {
+            Self::steer(animal, output);
         }
     }
 
+    fn steer(animal: &mut Animal, output: &[f32]) {
+        let speed = output[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
+        let rotation = output[1].clamp(-ROT_ACCEL, ROT_ACCEL);
+
+        animal.speed = (animal.speed + speed).clamp(-SPEED_MIN, SPEED_MAX);
+        animal.rotation = Rotation2::new(animal.rotation.angle() + rotation);
+    }
+
     pub fn handle_movement(&mut self) {
         for animal in &mut self.world.animals {
             animal.position += animal.rotation * nalgebra::Vector2::new(0.0, animal.speed);
@@ -1,6 +1,6 @@
 use rand::RngCore;
 
-use crate::{animal::Animal, food::Food};
+use crate::{animal::Animal, brain::BrainKind, food::Food};
 
 #[derive(Debug)]
 pub struct World {
@@ -9,8 +9,8 @@ pub struct World {
 }
 
 impl World {
-    pub fn random(rng: &mut dyn RngCore) -> Self {
-        let animals = (0..40).map(|_| Animal::random(rng)).collect();
+    pub fn random(rng: &mut dyn RngCore, brain: BrainKind) -> Self {
+        let animals = (0..40).map(|_| Animal::random(rng, brain)).collect();
         let foods = (0..60).map(|_| Food::random(rng)).collect();
         Self { animals, foods }
     }